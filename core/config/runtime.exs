@@ -134,6 +134,8 @@ config :canary, :openai_api_key, System.get_env("OPENAI_API_KEY")
 
 config :canary, :text_embedding_model, System.get_env("TEXT_EMBEDDING_MODEL")
 config :canary, :chat_completion_model, System.get_env("CHAT_COMPLETION_MODEL")
+config :canary, :chunk_tokenizer, System.get_env("CHUNK_TOKENIZER", "cl100k")
+config :canary, :prompt_tokenizer, System.get_env("PROMPT_TOKENIZER", "cl100k")
 
 if config_env() != :test do
   if System.get_env("GITHUB_CLIENT_ID") && System.get_env("GITHUB_CLIENT_SECRET") do
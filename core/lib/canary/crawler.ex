@@ -119,6 +119,8 @@ defmodule Canary.Crawler.Visitor do
   alias Canary.Crawler
   alias Canary.Sources.Webpage.Config
 
+  require Logger
+
   def run(%Config{} = config) do
     stream =
       config.start_urls
@@ -165,35 +167,27 @@ defmodule Canary.Crawler.Visitor do
       |> Enum.any?(&String.contains?(&1, "text/html"))
 
     if is_html do
-      crawler_config = opts[:crawler_config]
-
-      links =
-        find_links(url, body)
-        |> Enum.filter(&Crawler.include?(&1, crawler_config))
-
-      {:ok, links, state}
+      {:ok, find_links(url, body, opts[:crawler_config]), state}
     else
       {:ok, [], state}
     end
   end
 
-  defp find_links(url, html) do
-    case Floki.parse_document(html) do
-      {:ok, doc} ->
-        doc
-        |> Floki.find("a")
-        |> Floki.attribute("href")
-        |> Enum.map(fn href ->
-          URI.merge(url, href)
-          |> Map.put(:query, nil)
-          |> Map.put(:fragment, nil)
-          |> URI.to_string()
-          |> URI.encode()
-        end)
-        |> Enum.reject(&(URI.parse(&1).host != URI.parse(url).host))
+  defp find_links(url, html, %Config{} = config) do
+    filter = %{include: config.url_include_patterns, exclude: config.url_exclude_patterns}
+
+    case Canary.Native.extract_links(html, url, filter) do
+      {:ok, links} ->
+        host = URI.parse(url).host
+
+        links
+        |> Enum.map(&(URI.parse(&1.url) |> Map.put(:query, nil)))
+        |> Enum.filter(&(&1.host == host))
+        |> Enum.map(&URI.to_string/1)
         |> Enum.uniq()
 
-      _error ->
+      {:error, {kind, message}} ->
+        Logger.warning("extract_links failed (#{kind}): #{message}")
         []
     end
   end
@@ -1,4 +1,6 @@
 defmodule Canary.Keywords do
+  require Logger
+
   alias Canary.Sources.Document
   alias Canary.Sources.Webpage
   alias Canary.Sources.GithubIssue
@@ -11,7 +13,7 @@ defmodule Canary.Keywords do
       chunks
       |> Enum.map(fn %Ash.Union{value: value} -> value.content end)
       |> Enum.join("\n")
-      |> Canary.Native.extract_keywords(opts[:max] || 30)
+      |> extract_keywords(opts[:max] || 30)
 
     Enum.uniq(keywords_from_title ++ keywords_from_chunks)
   end
@@ -19,14 +21,25 @@ defmodule Canary.Keywords do
   def extract(%{title: nil}, _opts), do: []
 
   def extract(%Webpage.DocumentMeta{title: title}, _opts) do
-    Canary.Native.extract_keywords(title, 5)
+    extract_keywords(title, 5)
   end
 
   def extract(%GithubIssue.DocumentMeta{title: title}, _opts) do
-    Canary.Native.extract_keywords(title, 5)
+    extract_keywords(title, 5)
   end
 
   def extract(%GithubDiscussion.DocumentMeta{title: title}, _opts) do
-    Canary.Native.extract_keywords(title, 5)
+    extract_keywords(title, 5)
+  end
+
+  defp extract_keywords(content, n) do
+    case Canary.Native.extract_keywords(content, n) do
+      {:ok, keywords} ->
+        keywords
+
+      {:error, {kind, message}} ->
+        Logger.warning("extract_keywords failed (#{kind}): #{message}")
+        []
+    end
   end
 end
@@ -1,26 +1,250 @@
 defmodule Canary.Native do
   use Rustler, otp_app: :canary, crate: :canary_native
 
-  @spec chunk_text(String.t(), pos_integer()) :: [String.t()]
-  def chunk_text(_content, _max_tokens), do: error()
+  @typedoc """
+  Options are a map or keyword list. Anything else, or an option of the
+  wrong type, fails with `{:error, {:invalid_options, message}}`.
+  """
+  @type error :: {:error, {atom(), String.t()}}
 
-  @spec chunk_markdown(String.t(), pos_integer()) :: [String.t()]
-  def chunk_markdown(_content, _max_tokens), do: error()
+  @type chunk :: %{
+          text: String.t(),
+          content: String.t(),
+          start: non_neg_integer(),
+          end: non_neg_integer(),
+          tokens: non_neg_integer(),
+          headings: [String.t()]
+        }
 
-  @spec html_to_md(String.t()) :: String.t()
-  def html_to_md(_html), do: error()
+  @typedoc """
+  `:tokenizer` is `"cl100k"`, `"o200k"`, `"p50k"`, `"characters"`, `"words"`
+  or a path to a HuggingFace `tokenizer.json`.
 
-  @spec clone_depth(String.t(), String.t(), pos_integer()) :: boolean()
+  `header: true` prefixes each chunk's `:text` with `:title` and the heading
+  breadcrumb, joined by `:header_separator` (default `" > "`).
+  """
+  @type chunk_opts :: %{
+          optional(:tokenizer) => String.t(),
+          optional(:min_tokens) => pos_integer() | nil,
+          optional(:max_tokens) => pos_integer(),
+          optional(:overlap_tokens) => non_neg_integer(),
+          optional(:merge_below_tokens) => pos_integer() | nil,
+          optional(:header) => boolean(),
+          optional(:title) => String.t() | nil,
+          optional(:header_separator) => String.t()
+        }
+
+  @spec chunk_text(String.t(), chunk_opts()) :: {:ok, [chunk()]} | error()
+  def chunk_text(_content, _opts), do: error()
+
+  @spec chunk_markdown(String.t(), chunk_opts()) :: {:ok, [chunk()]} | error()
+  def chunk_markdown(_content, _opts), do: error()
+
+  @spec new_splitter(:text | :markdown, chunk_opts()) :: {:ok, reference()} | error()
+  def new_splitter(_kind, _opts), do: error()
+
+  @spec split(reference(), String.t()) :: {:ok, [chunk()]} | error()
+  def split(_splitter, _content), do: error()
+
+  @type linked_chunk :: %{
+          id: String.t(),
+          parent_id: String.t() | nil,
+          text: String.t(),
+          content: String.t(),
+          start: non_neg_integer(),
+          end: non_neg_integer(),
+          tokens: non_neg_integer(),
+          headings: [String.t()]
+        }
+
+  @typedoc """
+  Sections are cut at headings of `:section_level` or above (default `2`).
+  `:section` and `:passage` take `t:chunk_opts/0`, with `max_tokens`
+  defaulting to `1600` and `400`.
+  """
+  @type hierarchy_opts :: %{
+          optional(:section_level) => pos_integer(),
+          optional(:section) => chunk_opts(),
+          optional(:passage) => chunk_opts()
+        }
+
+  @doc """
+  Returns each section chunk followed by its passage chunks. Passages carry
+  the section's `:id` in `:parent_id`.
+  """
+  @spec chunk_hierarchical(String.t(), hierarchy_opts()) :: {:ok, [linked_chunk()]} | error()
+  def chunk_hierarchical(_content, _opts), do: error()
+
+  @type code_chunk :: %{
+          text: String.t(),
+          content: String.t(),
+          start: non_neg_integer(),
+          end: non_neg_integer(),
+          tokens: non_neg_integer(),
+          symbols: [String.t()]
+        }
+
+  @doc """
+  Splits source code at function, class and impl boundaries. `language` is a
+  name or file extension, e.g. `"rust"` or `"ex"`; `symbols` lists the
+  definitions enclosing each chunk, outermost first.
+  """
+  @spec chunk_code(String.t(), String.t(), chunk_opts()) :: {:ok, [code_chunk()]} | error()
+  def chunk_code(_content, _language, _opts), do: error()
+
+  @type front_matter :: %{
+          title: String.t() | nil,
+          description: String.t() | nil,
+          tags: [String.t()],
+          sidebar_position: float() | nil,
+          slug: String.t() | nil,
+          draft: boolean()
+        }
+
+  @type markdown_document :: %{
+          front_matter: front_matter(),
+          body: String.t(),
+          body_start: non_neg_integer()
+        }
+
+  @doc """
+  Separates YAML (`---`) or TOML (`+++`) front matter from the markdown body.
+  Files without front matter get default fields and the whole content as body,
+  as do files whose `---` block is not a mapping, e.g. a thematic break. Known
+  fields of the wrong type are converted where they can be (`"3"`, `yes`) and
+  left at their default otherwise.
+  """
+  @spec parse_markdown_document(String.t()) :: {:ok, markdown_document()} | error()
+  def parse_markdown_document(_content), do: error()
+
+  @typedoc """
+  `main_content: true` keeps only the page's primary content, dropping
+  sidebars, cookie banners, pagination and tables of contents. `include` and
+  `exclude` are CSS selectors of elements to keep and drop; an empty `exclude`
+  drops the boilerplate of common documentation themes.
+
+  Pages built with a known documentation framework get its content root,
+  chrome and tab markup handled by a preset, detected from the `generator`
+  meta tag or the page markup. `preset` forces one of `:docusaurus`,
+  `:vitepress`, `:mkdocs`, `:sphinx`, `:gitbook`, `:nextra` or `:mintlify`.
+
+  What is kept is the `include` matches when given, else the preset's content
+  root when the page has one, else the `main_content` extraction when enabled,
+  else the whole page.
+
+  Relative links and images are resolved against `url` (and `<base href>`);
+  `drop_external_links: true` turns links to other origins into plain text.
+  """
+  @type html_opts :: %{
+          optional(:main_content) => boolean(),
+          optional(:include) => [String.t()],
+          optional(:exclude) => [String.t()],
+          optional(:preset) => atom() | nil,
+          optional(:url) => String.t() | nil,
+          optional(:drop_external_links) => boolean()
+        }
+
+  @spec html_to_md(String.t(), html_opts()) :: {:ok, String.t()} | error()
+  def html_to_md(_html, _opts), do: error()
+
+  @type section :: %{
+          id: String.t() | nil,
+          level: 1..6,
+          title: String.t(),
+          content: String.t()
+        }
+
+  @doc """
+  Converts a page like `html_to_md/2` and splits the markdown at every heading.
+  Each section's `content` starts with its heading line; text before the first
+  heading is dropped.
+  """
+  @spec html_to_sections(String.t(), html_opts()) :: {:ok, [section()]} | error()
+  def html_to_sections(_html, _opts), do: error()
+
+  @type page_meta :: %{
+          title: String.t() | nil,
+          description: String.t() | nil,
+          canonical: String.t() | nil,
+          lang: String.t() | nil,
+          open_graph: %{String.t() => String.t()},
+          twitter: %{String.t() => String.t()},
+          robots: [String.t()],
+          noindex: boolean(),
+          alternates: [%{hreflang: String.t(), href: String.t()}],
+          generator: String.t() | nil
+        }
+
+  @doc """
+  Reads title, description, canonical URL, `lang`, OpenGraph (`og:*`) and
+  Twitter (`twitter:*`) fields, `robots` directives, `hreflang` alternates and
+  the generator from a page. Links are made absolute against `url`; pass `""`
+  to keep them as written.
+  """
+  @spec extract_page_meta(String.t(), String.t()) :: {:ok, page_meta()} | error()
+  def extract_page_meta(_html, _url), do: error()
+
+  @doc """
+  `extract_page_meta/2` and `html_to_sections/2` from a single parse, with
+  links in the metadata made absolute against the `url` option.
+  """
+  @spec html_to_page(String.t(), html_opts()) ::
+          {:ok, %{meta: page_meta(), sections: [section()]}} | error()
+  def html_to_page(_html, _opts), do: error()
+
+  @type link :: %{
+          url: String.t(),
+          text: String.t(),
+          rel: [String.t()],
+          same_origin: boolean()
+        }
+
+  @doc """
+  Lists the http(s) links of a page from `<a>`, `<area>` and `canonical`,
+  `next` and `prev` `<link>` elements, resolved against `url` and without
+  fragments. Links whose URL without the query matches an `exclude` glob, or
+  none of the `include` globs when given, are left out.
+  """
+  @spec extract_links(String.t(), String.t(), %{
+          optional(:include) => [String.t()],
+          optional(:exclude) => [String.t()]
+        }) :: {:ok, [link()]} | error()
+  def extract_links(_html, _url, _opts), do: error()
+
+  @spec clone_depth(String.t(), String.t(), pos_integer()) :: {:ok, String.t()} | error()
   def clone_depth(_repo_url, _dest_path, _depth), do: error()
 
-  @spec extract_keywords(String.t(), non_neg_integer()) :: list(String.t())
+  @spec extract_keywords(String.t(), non_neg_integer()) :: {:ok, list(String.t())} | error()
   def extract_keywords(_content, _n), do: error()
 
+  @type processed_page :: %{
+          url: String.t(),
+          markdown: String.t(),
+          chunks: [chunk()],
+          keywords: [String.t()]
+        }
+
+  @spec process_pages([{String.t(), String.t()}], map()) :: [{:ok, processed_page()} | error()]
+  def process_pages(_pages, _opts), do: error()
+
   @spec stopwords() :: list(String.t())
   def stopwords(), do: error()
 
   @spec glob_match(String.t(), String.t()) :: boolean()
   def glob_match(_pattern, _text), do: error()
 
+  @spec count_tokens(String.t(), String.t()) :: {:ok, non_neg_integer()} | error()
+  def count_tokens(_text, _tokenizer), do: error()
+
+  @doc """
+  Shortens `text` to at most `max_tokens`, removing text from the `side`
+  given: `:head` keeps the end, `:tail` keeps the beginning and `:middle`
+  keeps both ends. Cuts fall between words, and `:middle` keeps only the
+  beginning when the budget leaves no word on one side of the elision.
+  """
+  @spec truncate(String.t(), String.t(), non_neg_integer(), :head | :tail | :middle) ::
+          {:ok, String.t()} | error()
+  def truncate(_text, _tokenizer, _max_tokens, _side), do: error()
+
   defp error(), do: :erlang.nif_error(:nif_not_loaded)
 end
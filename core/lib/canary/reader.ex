@@ -1,39 +1,134 @@
 defmodule Canary.Reader do
+  require Logger
+
   def title_from_html(html) do
-    case Floki.parse_document(html) do
-      {:ok, document} ->
-        document
-        |> Floki.find("head title")
-        |> Floki.text()
-
-      _ ->
-        nil
+    case page_meta(html) do
+      {:ok, %{title: title}} -> title
+      _ -> nil
+    end
+  end
+
+  def page_meta(html, url \\ "") do
+    case Canary.Native.extract_page_meta(html, url || "") do
+      {:ok, _meta} = ok ->
+        ok
+
+      {:error, {kind, message}} = error ->
+        Logger.warning("extract_page_meta failed (#{kind}): #{message}")
+        error
+    end
+  end
+
+  def markdown_from_html(html, opts \\ []) do
+    case Canary.Native.html_to_md(html, html_opts(opts)) do
+      {:ok, md} ->
+        {:ok, String.trim(md)}
+
+      {:error, {kind, message}} = error ->
+        Logger.warning("html_to_md failed (#{kind}): #{message}")
+        error
+    end
+  end
+
+  def chunk_markdown(content, opts \\ []) do
+    opts = %{
+      max_tokens: 1600,
+      merge_below_tokens: 800,
+      tokenizer: tokenizer(),
+      header: Keyword.get(opts, :header, false),
+      title: opts[:title]
+    }
+
+    case Canary.Native.chunk_markdown(content, opts) do
+      {:ok, _chunks} = ok ->
+        ok
+
+      {:error, {kind, message}} = error ->
+        Logger.warning("chunk_markdown failed (#{kind}): #{message}")
+        error
     end
   end
 
-  def markdown_from_html(html) do
-    html
-    |> Canary.Native.html_to_md()
-    |> String.trim()
+  # Front matter stays out of the chunks: its title goes in the chunk header,
+  # its tags are returned for indexing, and drafts are skipped.
+  # No source ingests markdown files yet, so like `chunk_markdown/2` this has
+  # no caller until one does.
+  def chunk_markdown_document(content, opts \\ []) do
+    case Canary.Native.parse_markdown_document(content) do
+      {:ok, %{front_matter: %{draft: true}}} ->
+        :skip
+
+      {:ok, %{front_matter: front_matter, body: body}} ->
+        opts = Keyword.put_new(opts, :title, front_matter.title)
+
+        with {:ok, chunks} <- chunk_markdown(body, Keyword.put(opts, :header, true)) do
+          {:ok, %{front_matter: front_matter, chunks: chunks}}
+        end
+
+      {:error, {kind, message}} = error ->
+        Logger.warning("parse_markdown_document failed (#{kind}): #{message}")
+        error
+    end
   end
 
-  def chunk_markdown(content) do
-    content
-    |> Canary.Native.chunk_markdown(1600)
-    |> merge_small_chunks(1600 / 2)
+  # Sections with their passages, for retrieval that matches on a passage and
+  # returns its section. Indexing still stores flat chunks, so nothing calls
+  # this until the chunk schemas can hold the parent link.
+  def chunk_hierarchical(content) do
+    opts = %{
+      section: %{max_tokens: 1600, tokenizer: tokenizer()},
+      passage: %{max_tokens: 400, merge_below_tokens: 100, tokenizer: tokenizer()}
+    }
+
+    case Canary.Native.chunk_hierarchical(content, opts) do
+      {:ok, _chunks} = ok ->
+        ok
+
+      {:error, {kind, message}} = error ->
+        Logger.warning("chunk_hierarchical failed (#{kind}): #{message}")
+        error
+    end
   end
 
-  defp merge_small_chunks(chunks, min_size) do
-    chunks
-    |> Enum.reduce({[], nil}, fn cur_chunk, {acc, prev_chunk} ->
-      cond do
-        is_nil(prev_chunk) -> {acc, cur_chunk}
-        String.length(cur_chunk) < min_size -> {acc, prev_chunk <> "\n" <> cur_chunk}
-        String.length(prev_chunk) < min_size -> {acc, prev_chunk <> "\n" <> cur_chunk}
-        true -> {[prev_chunk | acc], cur_chunk}
-      end
+  # Batch counterpart of the html→chunks path for callers holding many pages at
+  # once. `Canary.Sources.Webpage.Fetcher` still indexes sections page by page
+  # as the crawler streams them, so it does not go through here.
+  def process_pages(pages, opts \\ []) do
+    opts =
+      opts
+      |> html_opts()
+      |> Map.merge(%{
+        max_tokens: opts[:max_tokens] || 1600,
+        merge_below_tokens: opts[:merge_below_tokens] || 800,
+        max_keywords: opts[:max_keywords] || 30,
+        tokenizer: opts[:tokenizer] || tokenizer()
+      })
+
+    pages
+    |> Canary.Native.process_pages(opts)
+    |> Enum.zip(pages)
+    |> Enum.flat_map(fn
+      {{:ok, page}, _} ->
+        [page]
+
+      {{:error, {kind, message}}, {url, _html}} ->
+        Logger.warning("processing #{url} failed (#{kind}): #{message}")
+        []
     end)
-    |> then(fn {acc, last_chunk} -> [last_chunk | acc] end)
-    |> Enum.reverse()
   end
+
+  # `:include_selectors`, `:exclude_selectors` and `:preset` come from the
+  # webpage source config.
+  defp html_opts(opts) do
+    %{
+      main_content: Keyword.get(opts, :main_content, true),
+      include: opts[:include_selectors] || [],
+      exclude: opts[:exclude_selectors] || [],
+      preset: opts[:preset],
+      url: opts[:url],
+      drop_external_links: Keyword.get(opts, :drop_external_links, false)
+    }
+  end
+
+  defp tokenizer, do: Application.get_env(:canary, :chunk_tokenizer, "cl100k")
 end
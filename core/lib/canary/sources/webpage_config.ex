@@ -3,11 +3,20 @@ defmodule Canary.Sources.Webpage.Config do
 
   alias Canary.Sources.Webpage.TagDefinition
 
+  @presets [:docusaurus, :vitepress, :mkdocs, :sphinx, :gitbook, :nextra, :mintlify]
+
+  def presets, do: @presets
+
   attributes do
     attribute :start_urls, {:array, :string}, default: []
     attribute :url_include_patterns, {:array, :string}, default: []
     attribute :url_exclude_patterns, {:array, :string}, default: []
     attribute :tag_definitions, {:array, TagDefinition}, default: []
+    attribute :include_selectors, {:array, :string}, default: []
+    attribute :exclude_selectors, {:array, :string}, default: []
+    attribute :main_content, :boolean, default: false
+
+    attribute :preset, :atom, constraints: [one_of: @presets]
   end
 
   actions do
@@ -15,12 +24,30 @@ defmodule Canary.Sources.Webpage.Config do
 
     create :create do
       primary? true
-      accept [:start_urls, :url_include_patterns, :url_exclude_patterns, :tag_definitions]
+      accept [
+        :start_urls,
+        :url_include_patterns,
+        :url_exclude_patterns,
+        :tag_definitions,
+        :include_selectors,
+        :exclude_selectors,
+        :main_content,
+        :preset
+      ]
     end
 
     update :update do
       primary? true
-      accept [:start_urls, :url_include_patterns, :url_exclude_patterns, :tag_definitions]
+      accept [
+        :start_urls,
+        :url_include_patterns,
+        :url_exclude_patterns,
+        :tag_definitions,
+        :include_selectors,
+        :exclude_selectors,
+        :main_content,
+        :preset
+      ]
     end
   end
 end
@@ -20,17 +20,18 @@ defmodule Canary.Sources.Webpage.Fetcher do
         stream =
           stream
           |> Stream.map(fn {url, html} ->
-            items = Canary.Scraper.run(html)
-
-            tags =
-              config.tag_definitions
-              |> Enum.filter(&is_matching_tag?(&1, url))
-              |> Enum.map(& &1.name)
-
-            if(length(items) == 0,
-              do: nil,
-              else: %Webpage.FetcherResult{url: url, html: html, tags: tags, items: items}
-            )
+            case Canary.Scraper.page(html, html_opts(config, url)) do
+              {:ok, %{meta: %{noindex: false}, items: [_ | _] = items}} ->
+                tags =
+                  config.tag_definitions
+                  |> Enum.filter(&is_matching_tag?(&1, url))
+                  |> Enum.map(& &1.name)
+
+                %Webpage.FetcherResult{url: url, html: html, tags: tags, items: items}
+
+              _ ->
+                nil
+            end
           end)
           |> Stream.reject(&is_nil/1)
 
@@ -41,6 +42,16 @@ defmodule Canary.Sources.Webpage.Fetcher do
     end
   end
 
+  defp html_opts(%Webpage.Config{} = config, url) do
+    %{
+      main_content: config.main_content || false,
+      include: config.include_selectors || [],
+      exclude: config.exclude_selectors || [],
+      preset: config.preset,
+      url: url
+    }
+  end
+
   defp is_matching_tag?(%Webpage.TagDefinition{} = tag, url) do
     tag.url_include_patterns
     |> Enum.any?(&Canary.Native.glob_match(&1, url))
@@ -1,39 +1,41 @@
 defmodule Canary.Tokenizer do
-  @tokenizer_ids %{
-    llama_2: "NousResearch/Llama-2-7b-hf"
-  }
+  @moduledoc """
+  Token counting and truncation for prompts.
 
-  def load!(:llama_2), do: load_tokenizer!(@tokenizer_ids.llama_2, nil)
+  `tokenizer` is a spec understood by `Canary.Native`: `"cl100k"`, `"o200k"`,
+  `"p50k"`, `"characters"`, `"words"` or a path to a HuggingFace
+  `tokenizer.json`.
+  """
 
-  defp load_tokenizer!(id, type) do
-    {:ok, tokenizer} = Bumblebee.load_tokenizer({:hf, id, cache_dir: "./.cache"}, type: type)
-    tokenizer
-  end
+  require Logger
 
-  def truncate(_text, _tokenizer, max_tokens) when max_tokens <= 0, do: ""
+  def default, do: Application.get_env(:canary, :prompt_tokenizer, "cl100k")
 
-  def truncate(text, tokenizer, max_tokens) do
-    total_ids = tokenizer |> Bumblebee.apply_tokenizer(text) |> Map.get("input_ids")
-    total_ids_size = Nx.shape(total_ids) |> elem(1)
+  def truncate(text, tokenizer, max_tokens, side \\ :head)
 
-    start_index = max(0, total_ids_size - max_tokens)
-    end_index = total_ids_size - start_index
+  def truncate(_text, _tokenizer, max_tokens, _side) when max_tokens <= 0, do: {:ok, ""}
 
-    truncated_ids = total_ids |> Nx.slice([0, start_index], [1, end_index])
+  def truncate(text, tokenizer, max_tokens, side) do
+    case Canary.Native.truncate(text, tokenizer, max_tokens, side) do
+      {:ok, _truncated} = ok ->
+        ok
 
-    tokenizer
-    |> Bumblebee.Tokenizer.decode(truncated_ids)
-    |> get_in([Access.at(0)])
-    |> String.trim()
+      {:error, {kind, message}} = error ->
+        Logger.warning("truncate failed (#{kind}): #{message}")
+        error
+    end
   end
 
-  def count_tokens("", _), do: 0
+  def count_tokens("", _), do: {:ok, 0}
 
   def count_tokens(text, tokenizer) do
-    tokenizer
-    |> Bumblebee.apply_tokenizer(text)
-    |> Map.get("token_type_ids")
-    |> Nx.shape()
-    |> elem(1)
+    case Canary.Native.count_tokens(text, tokenizer) do
+      {:ok, _count} = ok ->
+        ok
+
+      {:error, {kind, message}} = error ->
+        Logger.warning("count_tokens failed (#{kind}): #{message}")
+        error
+    end
   end
 end
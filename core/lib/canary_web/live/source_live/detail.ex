@@ -3,6 +3,16 @@ defmodule CanaryWeb.SourceLive.Detail do
   alias PrimerLive.Component, as: Primer
 
   @crawler_preview_id "webpage-crawler-preview"
+  @presets [
+    {"Auto-detect", ""},
+    {"Docusaurus", "docusaurus"},
+    {"VitePress", "vitepress"},
+    {"MkDocs", "mkdocs"},
+    {"Sphinx", "sphinx"},
+    {"GitBook", "gitbook"},
+    {"Nextra", "nextra"},
+    {"Mintlify", "mintlify"}
+  ]
 
   @impl true
   def render(assigns) do
@@ -127,6 +137,60 @@ defmodule CanaryWeb.SourceLive.Detail do
                   </Primer.button>
                 </.form_group>
 
+                <.form_group header="Keep CSS selectors">
+                  <%= for selector <- fc[:include_selectors].value || [] do %>
+                    <Primer.text_input
+                      type="text"
+                      name={fc[:include_selectors].name <> "[]"}
+                      value={selector}
+                      is_full_width
+                    />
+                  <% end %>
+                  <Primer.button
+                    type="button"
+                    phx-click={JS.dispatch("change")}
+                    name={fc[:include_selectors].name <> "[]"}
+                    phx-target={@myself}
+                    is_small
+                    is_full_width
+                  >
+                    <Primer.octicon name="plus-16" />
+                  </Primer.button>
+                </.form_group>
+
+                <.form_group header="Drop CSS selectors">
+                  <%= for selector <- fc[:exclude_selectors].value || [] do %>
+                    <Primer.text_input
+                      type="text"
+                      name={fc[:exclude_selectors].name <> "[]"}
+                      value={selector}
+                      is_full_width
+                    />
+                  <% end %>
+                  <Primer.button
+                    type="button"
+                    phx-click={JS.dispatch("change")}
+                    name={fc[:exclude_selectors].name <> "[]"}
+                    phx-target={@myself}
+                    is_small
+                    is_full_width
+                  >
+                    <Primer.octicon name="plus-16" />
+                  </Primer.button>
+                </.form_group>
+
+                <Primer.checkbox form={fc} field={:main_content}>
+                  <:label>Keep only the main content</:label>
+                  <:hint>Drops sidebars, banners and other chrome the selectors miss.</:hint>
+                </Primer.checkbox>
+
+                <Primer.select
+                  form={fc}
+                  field={:preset}
+                  options={@presets}
+                  form_control={%{label: "Documentation framework"}}
+                />
+
                 <.form_group header="Tags">
                   <div class="flex flex-col gap-6">
                     <%= for tag_def <- fc[:tag_definitions].value || [] do %>
@@ -321,6 +385,7 @@ defmodule CanaryWeb.SourceLive.Detail do
       |> assign(action_msg: if(source.state == :running, do: "Cancel", else: "Fetch"))
       |> assign(action_name: if(source.state == :running, do: "cancel", else: "fetch"))
       |> assign(crawler_preview_id: @crawler_preview_id)
+      |> assign(presets: @presets)
 
     {:ok, socket}
   end
@@ -422,7 +487,9 @@ defmodule CanaryWeb.SourceLive.Detail do
     [
       "start_urls",
       "url_include_patterns",
-      "url_exclude_patterns"
+      "url_exclude_patterns",
+      "include_selectors",
+      "exclude_selectors"
     ]
     |> Enum.reduce(params, fn key, acc ->
       if not is_list(acc["config"][key]) do
@@ -66,7 +66,11 @@ defmodule CanaryWeb.SourceLive.WebpageCrawlerPreview do
       start_urls: config["start_urls"],
       url_include_patterns: config["url_include_patterns"] || [],
       url_exclude_patterns: config["url_exclude_patterns"] || [],
-      tag_definitions: config["tag_definitions"] || []
+      tag_definitions: config["tag_definitions"] || [],
+      include_selectors: config["include_selectors"] || [],
+      exclude_selectors: config["exclude_selectors"] || [],
+      main_content: config["main_content"] in [true, "true"],
+      preset: preset(config["preset"])
     }
 
     self = self()
@@ -112,6 +116,11 @@ defmodule CanaryWeb.SourceLive.WebpageCrawlerPreview do
     {:noreply, socket}
   end
 
+  # Form input: anything but a supported preset means auto-detect.
+  defp preset(preset) do
+    Enum.find(Webpage.Config.presets(), &(Atom.to_string(&1) == preset))
+  end
+
   defp render_url(assigns) do
     ~H"""
     <.link href={@url}>
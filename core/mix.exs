@@ -89,7 +89,6 @@ defmodule Canary.MixProject do
       {:httpoison, "~> 2.2"},
       {:resend, "~> 0.4.2"},
       {:cachex, "~> 4.0"},
-      {:bumblebee, "~> 0.5.3"},
       {:absinthe_client, "~> 0.1.0"},
       {:cloak, "~> 1.1"},
       {:ash_cloak, "~> 0.1.2"},
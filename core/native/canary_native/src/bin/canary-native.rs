@@ -0,0 +1,365 @@
+//! Runs the ingestion pipeline on local files, so we can see exactly what the
+//! server indexed for a page without starting Phoenix.
+//!
+//! ```sh
+//! cargo run --features cli --bin canary-native -- html2md page.html
+//! curl -s https://docs.litellm.ai | canary-native chunk --max-tokens 200 --json
+//! ```
+
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use canary_native::{chunk, html, keywords, pipeline, tokenizer};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::json;
+
+#[derive(Parser)]
+#[command(
+    name = "canary-native",
+    about = "Run Canary's ingestion pipeline locally"
+)]
+struct Cli {
+    /// Print JSON instead of plain text.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Convert HTML to markdown.
+    Html2md {
+        /// Input file; reads stdin when omitted or `-`.
+        file: Option<PathBuf>,
+        /// Page URL that relative links are resolved against.
+        #[arg(long)]
+        url: Option<String>,
+        #[command(flatten)]
+        html: HtmlArgs,
+    },
+    /// Split HTML into one markdown section per heading, as webpage sources
+    /// are indexed.
+    Sections {
+        file: Option<PathBuf>,
+        #[arg(long)]
+        url: Option<String>,
+        #[command(flatten)]
+        html: HtmlArgs,
+    },
+    /// List the links of an HTML page, resolved against --url.
+    Links {
+        file: Option<PathBuf>,
+        #[arg(long, default_value = "")]
+        url: String,
+        /// Glob pattern of URLs to keep; repeatable.
+        #[arg(long)]
+        include: Vec<String>,
+        /// Glob pattern of URLs to drop; repeatable.
+        #[arg(long)]
+        exclude: Vec<String>,
+    },
+    /// Split text or markdown into chunks.
+    Chunk {
+        file: Option<PathBuf>,
+        #[arg(long, default_value_t = chunk::Options::default().max_tokens)]
+        max_tokens: usize,
+        #[arg(long)]
+        min_tokens: Option<usize>,
+        #[arg(long, default_value_t = 0)]
+        overlap_tokens: usize,
+        #[arg(long)]
+        merge_below_tokens: Option<usize>,
+        /// Prefix each chunk's text with the title and heading breadcrumb.
+        #[arg(long)]
+        header: bool,
+        /// Document title used in the header.
+        #[arg(long, requires = "header")]
+        title: Option<String>,
+        /// cl100k, o200k, p50k, characters, words or a tokenizer.json path.
+        #[arg(long, default_value = tokenizer::DEFAULT)]
+        tokenizer: String,
+        #[arg(long, value_enum, default_value_t = Mode::Markdown)]
+        mode: Mode,
+        /// Chunk as source code in this language, e.g. rust or ex; overrides --mode.
+        #[arg(long)]
+        language: Option<String>,
+    },
+    /// Extract keywords.
+    Keywords {
+        file: Option<PathBuf>,
+        #[arg(short, default_value_t = 30)]
+        n: usize,
+    },
+    /// Check paths or URLs against a glob pattern; reads them from stdin when omitted.
+    Glob { pattern: String, paths: Vec<String> },
+    /// Run html→markdown→chunks→keywords on an HTML page, like
+    /// `Canary.Reader.process_pages`.
+    Process {
+        file: Option<PathBuf>,
+        #[arg(long, default_value = "")]
+        url: String,
+        #[command(flatten)]
+        html: HtmlArgs,
+        #[arg(long, default_value_t = chunk::Options::default().max_tokens)]
+        max_tokens: usize,
+        #[arg(long, default_value = tokenizer::DEFAULT)]
+        tokenizer: String,
+        #[arg(long, default_value_t = pipeline::Options::default().max_keywords)]
+        max_keywords: usize,
+    },
+}
+
+#[derive(Args)]
+struct HtmlArgs {
+    /// Keep only the page's main content. Off by default, as for webpage
+    /// sources.
+    #[arg(long)]
+    main_content: bool,
+    /// CSS selector of elements to keep; repeatable.
+    #[arg(long)]
+    include: Vec<String>,
+    /// CSS selector of elements to drop; repeatable.
+    #[arg(long)]
+    exclude: Vec<String>,
+    /// Turn links to other sites into plain text.
+    #[arg(long)]
+    drop_external_links: bool,
+    /// docusaurus, vitepress, mkdocs, sphinx, gitbook, nextra or mintlify;
+    /// detected from the page when omitted.
+    #[arg(long)]
+    preset: Option<html::Preset>,
+}
+
+impl From<HtmlArgs> for html::Options {
+    fn from(args: HtmlArgs) -> Self {
+        Self {
+            main_content: args.main_content,
+            include: args.include,
+            exclude: args.exclude,
+            url: None,
+            drop_external_links: args.drop_external_links,
+            preset: args.preset,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Mode {
+    Text,
+    Markdown,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    match cli.command {
+        Command::Html2md { file, url, html } => {
+            let opts = html::Options { url, ..html.into() };
+            let md = html::to_md(&read_input(file)?, &opts)?;
+
+            if cli.json {
+                println!("{}", json!({ "markdown": md }));
+            } else {
+                println!("{md}");
+            }
+        }
+        Command::Sections { file, url, html } => {
+            let opts = html::Options { url, ..html.into() };
+            let sections = html::to_sections(&read_input(file)?, &opts)?;
+
+            if cli.json {
+                let sections: Vec<_> = sections
+                    .iter()
+                    .map(|section| {
+                        json!({
+                            "id": section.id,
+                            "level": section.level,
+                            "title": section.title,
+                            "content": section.content,
+                        })
+                    })
+                    .collect();
+                println!("{}", json!(sections));
+            } else {
+                for section in &sections {
+                    println!("{}\n", section.content);
+                }
+            }
+        }
+        Command::Links {
+            file,
+            url,
+            include,
+            exclude,
+        } => {
+            let filter = html::LinkFilter { include, exclude };
+            let links = html::links(&read_input(file)?, &url, &filter)?;
+
+            if cli.json {
+                let links: Vec<_> = links
+                    .iter()
+                    .map(|link| {
+                        json!({
+                            "url": link.url,
+                            "text": link.text,
+                            "rel": link.rel,
+                            "same_origin": link.same_origin,
+                        })
+                    })
+                    .collect();
+                println!("{}", json!(links));
+            } else {
+                for link in &links {
+                    println!("{}\t{}", link.url, link.text);
+                }
+            }
+        }
+        Command::Chunk {
+            file,
+            max_tokens,
+            min_tokens,
+            overlap_tokens,
+            merge_below_tokens,
+            header,
+            title,
+            tokenizer,
+            mode,
+            language,
+        } => {
+            let content = read_input(file)?;
+            let opts = chunk::Options {
+                tokenizer,
+                min_tokens,
+                max_tokens,
+                overlap_tokens,
+                merge_below_tokens,
+                header: header.then(|| chunk::Header {
+                    title,
+                    ..chunk::Header::default()
+                }),
+            };
+            let chunks = match (language, mode) {
+                (Some(language), _) => chunk::chunk_code(&content, language.parse()?, &opts)?,
+                (None, Mode::Text) => chunk::chunk_text(&content, &opts)?,
+                (None, Mode::Markdown) => chunk::chunk_markdown(&content, &opts)?,
+            };
+
+            if cli.json {
+                let chunks: Vec<_> = chunks.iter().map(chunk_json).collect();
+                println!("{}", json!(chunks));
+            } else {
+                print_chunks(&chunks);
+            }
+        }
+        Command::Keywords { file, n } => {
+            let words = keywords::extract(&read_input(file)?, n)?;
+
+            if cli.json {
+                println!("{}", json!(words));
+            } else {
+                println!("{}", words.join("\n"));
+            }
+        }
+        Command::Glob { pattern, paths } => {
+            let paths = if paths.is_empty() {
+                read_input(None)?.lines().map(String::from).collect()
+            } else {
+                paths
+            };
+
+            for path in paths {
+                let matched = canary_native::glob_match(&pattern, &path);
+
+                if cli.json {
+                    println!("{}", json!({ "path": path, "matched": matched }));
+                } else if matched {
+                    println!("{path}");
+                }
+            }
+        }
+        Command::Process {
+            file,
+            url,
+            html,
+            max_tokens,
+            tokenizer,
+            max_keywords,
+        } => {
+            let opts = pipeline::Options {
+                html: html.into(),
+                chunk: chunk::Options {
+                    tokenizer,
+                    ..chunk::Options::new(max_tokens)
+                },
+                max_keywords,
+            };
+            let page = pipeline::process_page(&url, &read_input(file)?, &opts)?;
+
+            if cli.json {
+                let value = json!({
+                    "url": page.url,
+                    "markdown": page.markdown,
+                    "chunks": page.chunks.iter().map(chunk_json).collect::<Vec<_>>(),
+                    "keywords": page.keywords,
+                });
+                println!("{value}");
+            } else {
+                print_chunks(&page.chunks);
+                println!("\n---\n\nkeywords: {}", page.keywords.join(", "));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn chunk_json(chunk: &chunk::Chunk) -> serde_json::Value {
+    json!({
+        "text": chunk.text,
+        "content": chunk.content,
+        "start": chunk.start,
+        "end": chunk.end,
+        "tokens": chunk.tokens,
+        "headings": chunk.headings,
+    })
+}
+
+fn print_chunks(chunks: &[chunk::Chunk]) {
+    for (i, chunk) in chunks.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!(
+            "--- {}..{} ({} tokens) {}",
+            chunk.start,
+            chunk.end,
+            chunk.tokens,
+            chunk.headings.join(" > ")
+        );
+        println!("{}", chunk.text);
+    }
+}
+
+fn read_input(file: Option<PathBuf>) -> io::Result<String> {
+    match file {
+        Some(path) if path.as_os_str() != "-" => std::fs::read_to_string(path),
+        _ => {
+            let mut buf = String::new();
+            io::stdin().read_to_string(&mut buf)?;
+            Ok(buf)
+        }
+    }
+}
@@ -1,18 +1,610 @@
-use text_splitter::{ChunkConfig, MarkdownSplitter, TextSplitter};
-use tiktoken_rs::cl100k_base;
+//! Token-sized chunking of plain text, markdown and source code.
 
-pub fn chunk_text<'a>(content: &'a str, max_tokens: usize) -> anyhow::Result<Vec<String>> {
-    let tokenizer = cl100k_base().unwrap();
+use std::ops::Range;
 
-    let splitter = TextSplitter::new(ChunkConfig::new(max_tokens).with_sizer(tokenizer));
-    let chunks = splitter.chunks(content);
-    Ok(chunks.map(|chunk| chunk.to_string()).collect())
+use text_splitter::{ChunkConfig, ChunkSizer, CodeSplitter, MarkdownSplitter, TextSplitter};
+
+use crate::tokenizer::{self, Tokenizer};
+use crate::{code, markdown};
+
+mod hierarchy;
+
+pub use hierarchy::{chunk_hierarchical, HierarchyOptions, LinkedChunk};
+
+/// Errors returned while chunking.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Tokenizer(#[from] tokenizer::Error),
+    #[error("invalid chunk options: {0}")]
+    InvalidOptions(String),
+    #[error(transparent)]
+    Code(#[from] code::Error),
+}
+
+impl Error {
+    /// Short machine-readable name of the error, e.g. `"tokenizer"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Tokenizer(e) => e.kind(),
+            Error::InvalidOptions(_) => "invalid_options",
+            Error::Code(e) => e.kind(),
+        }
+    }
+}
+
+/// How big chunks should be, and how their size is measured.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// Tokenizer spec, see [`tokenizer`]. Defaults to [`tokenizer::DEFAULT`].
+    pub tokenizer: String,
+    /// Desired chunk size. Chunks are filled up to this size, and only grow
+    /// past it (up to `max_tokens`) to stay at a higher semantic level.
+    /// Defaults to `max_tokens`.
+    pub min_tokens: Option<usize>,
+    pub max_tokens: usize,
+    /// Tokens shared between the end of a chunk and the start of the next one.
+    pub overlap_tokens: usize,
+    /// Chunks smaller than this are merged with their next neighbour, as long
+    /// as the result stays within `max_tokens`. Off when `None`.
+    pub merge_below_tokens: Option<usize>,
+    /// Context prepended to each chunk's `text`. Its token cost comes out of
+    /// the chunk budget. Off when `None`.
+    pub header: Option<Header>,
+}
+
+impl Options {
+    pub fn new(max_tokens: usize) -> Self {
+        Self {
+            tokenizer: tokenizer::DEFAULT.to_string(),
+            min_tokens: None,
+            max_tokens,
+            overlap_tokens: 0,
+            merge_below_tokens: None,
+            header: None,
+        }
+    }
+
+    /// Chunk config with `reserve` tokens set aside for the contextual header.
+    fn config<Sizer: ChunkSizer>(
+        &self,
+        sizer: Sizer,
+        reserve: usize,
+    ) -> Result<ChunkConfig<Sizer>, Error> {
+        let max = self.max_tokens;
+        let min = self.min_tokens.unwrap_or(max);
+
+        if max == 0 {
+            return Err(Error::InvalidOptions(
+                "max_tokens must be greater than 0".to_string(),
+            ));
+        }
+        if min == 0 || min > max {
+            return Err(Error::InvalidOptions(format!(
+                "min_tokens must be between 1 and max_tokens ({max}), got {min}"
+            )));
+        }
+        if self.overlap_tokens >= min {
+            return Err(Error::InvalidOptions(format!(
+                "overlap_tokens must be less than min_tokens ({min}), got {}",
+                self.overlap_tokens
+            )));
+        }
+
+        if reserve > 0 && (reserve >= min || self.overlap_tokens >= min - reserve) {
+            return Err(Error::InvalidOptions(format!(
+                "header takes up to {reserve} tokens, leaving no room for content within min_tokens ({min})"
+            )));
+        }
+
+        ChunkConfig::new(min - reserve..=max - reserve)
+            .with_sizer(sizer)
+            .with_overlap(self.overlap_tokens)
+            .map_err(|e| Error::InvalidOptions(e.to_string()))
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new(1600)
+    }
+}
+
+/// Contextual header: the document title followed by the chunk's heading
+/// breadcrumb, e.g. `LiteLLM > Trimming Input Messages > Usage`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub title: Option<String>,
+    /// Placed between the title and each heading.
+    pub separator: String,
+}
+
+impl Header {
+    fn render(&self, headings: &[String]) -> String {
+        let parts: Vec<&str> = self
+            .title
+            .iter()
+            .filter(|title| !title.is_empty())
+            .chain(headings)
+            .map(String::as_str)
+            .collect();
+
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!("{}\n\n", parts.join(&self.separator))
+        }
+    }
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Self {
+            title: None,
+            separator: " > ".to_string(),
+        }
+    }
 }
 
-pub fn chunk_markdown<'a>(content: &'a str, max_tokens: usize) -> anyhow::Result<Vec<String>> {
-    let tokenizer = cl100k_base()?;
+/// A chunk of the source document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// Text to index: `content` behind the contextual header, if any.
+    pub text: String,
+    /// The source text between `start` and `end`, reworked for markdown so
+    /// each chunk is valid on its own: a fence it cuts through is re-opened
+    /// at the top and closed at the bottom, a table it continues gets its
+    /// header rows repeated, a fence's closing line it starts on is dropped
+    /// and blockquote markers before `start` are kept. Markdown `content` can
+    /// therefore differ from the source slice.
+    pub content: String,
+    /// Byte offset in the source of the first byte the chunk covers.
+    pub start: usize,
+    /// Byte offset in the source one past the last byte the chunk covers.
+    pub end: usize,
+    /// Size of `text` under the splitter's tokenizer.
+    pub tokens: usize,
+    /// Titles of the headings in effect at `start`, outermost first. For
+    /// source code, names of the symbols enclosing `start` instead.
+    /// Always empty for plain text.
+    pub headings: Vec<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct Structure {
+    pub headings: Vec<markdown::Heading>,
+    pub fences: Vec<markdown::Fence>,
+    pub tables: Vec<markdown::Table>,
+    pub symbols: Vec<code::Symbol>,
+}
+
+impl Structure {
+    fn breadcrumb(&self, offset: usize) -> Vec<String> {
+        if self.symbols.is_empty() {
+            markdown::breadcrumb(&self.headings, offset)
+        } else {
+            code::symbol_path(&self.symbols, offset)
+        }
+    }
+
+    /// Every breadcrumb a chunk of the document can get.
+    fn breadcrumbs(&self) -> impl Iterator<Item = Vec<String>> + '_ {
+        let starts = self
+            .headings
+            .iter()
+            .map(|heading| heading.start)
+            .chain(self.symbols.iter().map(|symbol| symbol.start));
+
+        std::iter::once(Vec::new()).chain(starts.map(|start| self.breadcrumb(start)))
+    }
+}
+
+/// A splitter built once and reused across calls.
+pub struct Splitter {
+    inner: Inner,
+    sizer: Tokenizer,
+    opts: Options,
+}
+
+enum Inner {
+    Text(TextSplitter<Tokenizer>),
+    Markdown(MarkdownSplitter<Tokenizer>),
+    Code(CodeSplitter<Tokenizer>, code::Language),
+}
+
+impl Inner {
+    fn code(language: code::Language, config: ChunkConfig<Tokenizer>) -> Result<Self, Error> {
+        let splitter = CodeSplitter::new(language.grammar(), config)
+            .map_err(|e| code::Error::Grammar(e.to_string()))?;
+
+        Ok(Inner::Code(splitter, language))
+    }
+
+    /// Same kind of splitter, with a different config.
+    fn with_config(&self, config: ChunkConfig<Tokenizer>) -> Result<Self, Error> {
+        Ok(match self {
+            Inner::Text(_) => Inner::Text(TextSplitter::new(config)),
+            Inner::Markdown(_) => Inner::Markdown(MarkdownSplitter::new(config)),
+            Inner::Code(_, language) => Inner::code(*language, config)?,
+        })
+    }
+}
+
+impl Splitter {
+    /// Splits on sentences, words and line breaks.
+    pub fn text(opts: &Options) -> Result<Self, Error> {
+        let sizer = Tokenizer::load(&opts.tokenizer)?;
+        let config = opts.config(sizer.clone(), 0)?;
+
+        Ok(Self {
+            inner: Inner::Text(TextSplitter::new(config)),
+            sizer,
+            opts: opts.clone(),
+        })
+    }
+
+    /// Splits on markdown structure first (headings, blocks, lists).
+    ///
+    /// Chunks that are only a heading or only the opening line of a code
+    /// fence are always attached to the chunk that follows them, and fences
+    /// cut by a chunk boundary are re-opened and closed. Tables are only cut
+    /// between rows, and each continuation starts with the header rows
+    /// again. The splitter sizes chunks before any of this, so an attached
+    /// line, a re-opened fence or a repeated header can take a chunk past
+    /// `max_tokens` by its own length. Merging small chunks measures them and
+    /// stays within it.
+    pub fn markdown(opts: &Options) -> Result<Self, Error> {
+        let sizer = Tokenizer::load(&opts.tokenizer)?;
+        let config = opts.config(sizer.clone(), 0)?;
+
+        Ok(Self {
+            inner: Inner::Markdown(MarkdownSplitter::new(config)),
+            sizer,
+            opts: opts.clone(),
+        })
+    }
+
+    /// Splits source code at syntax tree boundaries, keeping functions,
+    /// classes and impls whole where they fit.
+    pub fn code(language: code::Language, opts: &Options) -> Result<Self, Error> {
+        let sizer = Tokenizer::load(&opts.tokenizer)?;
+        let config = opts.config(sizer.clone(), 0)?;
+
+        Ok(Self {
+            inner: Inner::code(language, config)?,
+            sizer,
+            opts: opts.clone(),
+        })
+    }
+
+    /// Splits `content` into chunks sized according to the splitter's [`Options`].
+    ///
+    /// With a header configured, the room it needs depends on the document's
+    /// headings, so the chunk budget is worked out again for every document.
+    pub fn chunks(&self, content: &str) -> Result<Vec<Chunk>, Error> {
+        let structure = self.structure(content);
+        self.chunks_in(content, 0..content.len(), &structure)
+    }
+
+    /// Headings and fences of markdown, symbols of source code, or nothing
+    /// for plain text.
+    pub(crate) fn structure(&self, content: &str) -> Structure {
+        match self.inner {
+            Inner::Markdown(_) => Structure {
+                headings: markdown::headings(content),
+                fences: markdown::fences(content),
+                tables: markdown::tables(content),
+                ..Structure::default()
+            },
+            Inner::Code(_, language) => Structure {
+                symbols: code::symbols(content, language),
+                ..Structure::default()
+            },
+            Inner::Text(_) => Structure::default(),
+        }
+    }
+
+    /// Splits just `range` of `content`, keeping offsets, breadcrumbs and
+    /// fences relative to the whole document.
+    pub(crate) fn chunks_in(
+        &self,
+        content: &str,
+        range: Range<usize>,
+        structure: &Structure,
+    ) -> Result<Vec<Chunk>, Error> {
+        // Sized with a fresh failure slot, as calls may share the splitter.
+        let sizer = self.sizer.fresh();
+        let reserve = self.header_reserve(&sizer, structure);
+        let inner = self
+            .inner
+            .with_config(self.opts.config(sizer.clone(), reserve)?)?;
+
+        let text = &content[range.clone()];
+        let ranges = match &inner {
+            Inner::Text(splitter) => ranges(range.start, splitter.chunk_indices(text)),
+            Inner::Markdown(splitter) => {
+                let ranges = ranges(range.start, splitter.chunk_indices(text));
+                let ranges = within_quotes(content, ranges);
+                let ranges = between_rows(content, &structure.tables, ranges);
+                attach_dangling(content, &structure.tables, ranges)
+            }
+            Inner::Code(splitter, _) => ranges(range.start, splitter.chunk_indices(text)),
+        };
+
+        let max_tokens = self.opts.max_tokens - reserve;
+        let chunks = self
+            .merge_small(&sizer, content, structure, ranges, max_tokens)
+            .into_iter()
+            .map(|range| {
+                let body = body(content, structure, range.clone());
+                let headings = structure.breadcrumb(range.start);
+                self.chunk(&sizer, body, range, headings)
+            })
+            .collect();
+
+        // Sizing cannot fail mid-split, so tokenizer errors surface here.
+        sizer.check()?;
+        Ok(chunks)
+    }
+
+    fn chunk(
+        &self,
+        sizer: &Tokenizer,
+        content: String,
+        range: Range<usize>,
+        headings: Vec<String>,
+    ) -> Chunk {
+        let header = match &self.opts.header {
+            Some(header) => header.render(&headings),
+            None => String::new(),
+        };
+        let text = header + &content;
+
+        Chunk {
+            tokens: sizer.size(&text),
+            text,
+            content,
+            start: range.start,
+            end: range.end,
+            headings,
+        }
+    }
+
+    /// Tokens taken by the longest header any chunk of the document can get.
+    fn header_reserve(&self, sizer: &Tokenizer, structure: &Structure) -> usize {
+        let Some(header) = &self.opts.header else {
+            return 0;
+        };
+
+        structure
+            .breadcrumbs()
+            .map(|path| sizer.size(&header.render(&path)))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Greedily merges each chunk under `merge_below_tokens` with the next one
+    /// while the merged chunk fits in `max_tokens`. Chunks are measured as
+    /// they will read, with re-opened fences and repeated table headers.
+    fn merge_small(
+        &self,
+        sizer: &Tokenizer,
+        content: &str,
+        structure: &Structure,
+        ranges: Vec<Range<usize>>,
+        max_tokens: usize,
+    ) -> Vec<Range<usize>> {
+        let Some(threshold) = self.opts.merge_below_tokens else {
+            return ranges;
+        };
+
+        let size = |range: Range<usize>| sizer.size(&body(content, structure, range));
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+
+        for range in ranges {
+            if let Some(last) = merged.last_mut() {
+                let small = size(last.clone()) < threshold || size(range.clone()) < threshold;
+
+                if small && size(last.start..range.end) <= max_tokens {
+                    last.end = range.end;
+                    continue;
+                }
+            }
+            merged.push(range);
+        }
+
+        merged
+    }
+}
+
+fn ranges<'a>(offset: usize, indices: impl Iterator<Item = (usize, &'a str)>) -> Vec<Range<usize>> {
+    indices
+        .map(|(start, text)| offset + start..offset + start + text.len())
+        .collect()
+}
+
+/// Attaches heading-only, fence-opener, quote-marker and table-header chunks
+/// to the chunk after them. Trailing ones, with nothing after them, go to the chunk before
+/// instead.
+fn attach_dangling(
+    content: &str,
+    tables: &[markdown::Table],
+    ranges: Vec<Range<usize>>,
+) -> Vec<Range<usize>> {
+    let mut attached: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    let mut pending: Option<Range<usize>> = None;
+
+    for range in ranges {
+        let text = &content[range.clone()];
+
+        let table_header = tables
+            .iter()
+            .any(|table| table.header_end() == range.end && !table.rows.is_empty());
+
+        if markdown::is_heading_only(text)
+            || markdown::is_fence_opener(text)
+            || markdown::is_quote_marker(text)
+            || table_header
+        {
+            pending = Some(pending.map_or(range.clone(), |p| p.start..range.end));
+        } else {
+            let start = pending.take().map_or(range.start, |p| p.start);
+            attached.push(start..range.end);
+        }
+    }
+
+    if let Some(pending) = pending {
+        match attached.last_mut() {
+            Some(last) => last.end = pending.end,
+            None => attached.push(pending),
+        }
+    }
+
+    attached
+}
+
+/// Moves chunk boundaries that fall among a line's blockquote markers to the
+/// line's start, so a chunk never starts mid-quote without its `> ` or ends
+/// on the next line's markers.
+fn within_quotes(content: &str, ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    let markers = |from: usize, to: usize| {
+        let text = &content[from..to];
+        text.contains('>') && markdown::strip_container(text).is_empty()
+    };
+    let mut snapped: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+
+    for range in ranges {
+        let line = markdown::line_start(content, range.start);
+        let start = if markers(line, range.start) {
+            line
+        } else {
+            range.start
+        };
+        let line = markdown::line_start(content, range.end);
+        let end = if line > start && markers(line, range.end) {
+            content[..line].trim_end().len()
+        } else {
+            range.end
+        };
+
+        if start < end {
+            snapped.push(start..end);
+        }
+    }
+
+    snapped
+}
+
+/// Moves chunk boundaries that fall inside a table row to the start of that
+/// row, so rows are never cut. A range holding less than a single row keeps
+/// the whole row instead.
+fn between_rows(
+    content: &str,
+    tables: &[markdown::Table],
+    ranges: Vec<Range<usize>>,
+) -> Vec<Range<usize>> {
+    let row_at = |offset: usize| tables.iter().find_map(|table| table.row_at(offset));
+    let mut snapped: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+
+    for range in ranges {
+        let mut start = range.start;
+        if let Some(row) = row_at(start) {
+            let taken = snapped.last().is_some_and(|last| last.end >= row.end);
+            start = if taken {
+                let rest = &content[row.end..];
+                row.end + (rest.len() - rest.trim_start().len())
+            } else {
+                row.start
+            };
+        }
+        let end = match row_at(range.end) {
+            Some(row) if row.start > start => start + content[start..row.start].trim_end().len(),
+            Some(row) => row.end,
+            None => range.end,
+        };
+
+        if start < end {
+            snapped.push(start..end);
+        }
+    }
+
+    snapped
+}
+
+/// What a chunk over `range` holds: the header rows of a table it continues,
+/// then the range with the fences it cuts re-opened and closed.
+fn body(content: &str, structure: &Structure, range: Range<usize>) -> String {
+    table_header(&structure.tables, range.start) + &fenced(content, &structure.fences, range)
+}
+
+/// Header rows of the table `offset` continues, followed by a line break,
+/// or nothing outside tables.
+fn table_header(tables: &[markdown::Table], offset: usize) -> String {
+    tables
+        .iter()
+        .find(|table| table.continues_at(offset))
+        .map_or(String::new(), |table| format!("{}\n", table.header))
+}
+
+/// Copies `range` out of `content`, re-opening a fence the range starts in
+/// and closing one it ends in, inside the same blockquote or list as the
+/// fence. A range starting on the closing line is past the fence and drops
+/// that line, which would open a fence on its own; one ending before the
+/// closing marker still needs the closer.
+fn fenced(content: &str, fences: &[markdown::Fence], range: Range<usize>) -> String {
+    let text = fences
+        .iter()
+        .find(|fence| fence.body_end <= range.start && range.start < fence.end)
+        .and_then(|fence| content.get(fence.end..range.end))
+        .map(str::trim_start)
+        .filter(|rest| !rest.is_empty())
+        .unwrap_or(&content[range.clone()]);
+    let opened = fences
+        .iter()
+        .find(|fence| fence.start < range.start && range.start < fence.body_end);
+    let closed = fences.iter().find(|fence| {
+        fence.start < range.end
+            && range.end < fence.end
+            && markdown::strip_container(&content[fence.body_end.min(range.end)..range.end])
+                .is_empty()
+    });
+
+    let mut fenced = String::with_capacity(text.len());
+    if let Some(fence) = opened {
+        fenced.push_str(&fence.prefix);
+        fenced.push_str(&fence.opener);
+        fenced.push('\n');
+        // The range may start past the line's `> ` markers.
+        let lead = &content[markdown::line_start(content, range.start)..range.start];
+        if markdown::strip_container(lead).is_empty() {
+            fenced.push_str(lead);
+        }
+    }
+    fenced.push_str(text);
+    if let Some(fence) = closed {
+        fenced.push('\n');
+        fenced.push_str(&fence.prefix);
+        fenced.push_str(&fence.closer);
+    }
+
+    fenced
+}
+
+/// Splits plain text into chunks.
+pub fn chunk_text(content: &str, opts: &Options) -> Result<Vec<Chunk>, Error> {
+    Splitter::text(opts)?.chunks(content)
+}
+
+/// Splits source code into chunks.
+pub fn chunk_code(
+    content: &str,
+    language: code::Language,
+    opts: &Options,
+) -> Result<Vec<Chunk>, Error> {
+    Splitter::code(language, opts)?.chunks(content)
+}
 
-    let splitter = MarkdownSplitter::new(ChunkConfig::new(max_tokens).with_sizer(tokenizer));
-    let chunks = splitter.chunks(content);
-    Ok(chunks.map(|chunk| chunk.to_string()).collect())
+/// Splits markdown into chunks.
+pub fn chunk_markdown(content: &str, opts: &Options) -> Result<Vec<Chunk>, Error> {
+    Splitter::markdown(opts)?.chunks(content)
 }
@@ -0,0 +1,109 @@
+//! Two-level chunking: whole sections for context, small passages for search.
+
+use std::collections::HashSet;
+
+use super::{Chunk, Error, Options, Splitter};
+use crate::markdown;
+
+/// Sizing for both levels of [`chunk_hierarchical`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchyOptions {
+    /// Sections are cut at headings of this level or above, so `2` starts a
+    /// new section at every H1 and H2.
+    pub section_level: usize,
+    /// Sections bigger than `section.max_tokens` become several parents.
+    pub section: Options,
+    pub passage: Options,
+}
+
+impl Default for HierarchyOptions {
+    fn default() -> Self {
+        Self {
+            section_level: 2,
+            section: Options::default(),
+            passage: Options::new(400),
+        }
+    }
+}
+
+/// A chunk with a stable id. Passages point at the section they came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkedChunk {
+    /// Derived from the chunk's headings and content, so re-chunking an
+    /// unchanged section gives the same ids.
+    pub id: String,
+    /// `None` for sections.
+    pub parent_id: Option<String>,
+    pub chunk: Chunk,
+}
+
+/// Splits markdown into section chunks, each followed by the passage chunks
+/// inside it.
+pub fn chunk_hierarchical(
+    content: &str,
+    opts: &HierarchyOptions,
+) -> Result<Vec<LinkedChunk>, Error> {
+    let sections = Splitter::markdown(&opts.section)?;
+    let passages = Splitter::markdown(&opts.passage)?;
+
+    let structure = sections.structure(content);
+    let mut ids = Ids::default();
+    let mut linked = Vec::new();
+
+    for range in markdown::sections(content, &structure.headings, opts.section_level) {
+        for section in sections.chunks_in(content, range, &structure)? {
+            let id = ids.next(&[&section.headings.join("\n"), &section.content]);
+            let children = passages.chunks_in(content, section.start..section.end, &structure)?;
+
+            linked.push(LinkedChunk {
+                id: id.clone(),
+                parent_id: None,
+                chunk: section,
+            });
+            for passage in children {
+                linked.push(LinkedChunk {
+                    id: ids.next(&[&id, &passage.content]),
+                    parent_id: Some(id.clone()),
+                    chunk: passage,
+                });
+            }
+        }
+    }
+
+    Ok(linked)
+}
+
+/// Hands out content-derived ids, bumping the hash on collisions so that
+/// repeated content still gets distinct ids.
+#[derive(Default)]
+struct Ids(HashSet<u64>);
+
+impl Ids {
+    fn next(&mut self, parts: &[&str]) -> String {
+        let mut hash = fnv1a(parts);
+
+        while !self.0.insert(hash) {
+            hash = hash.wrapping_add(1);
+        }
+
+        format!("{hash:016x}")
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a over `parts`, with a zero byte after each so that `["ab", "c"]`
+/// and `["a", "bc"]` differ. Unlike `DefaultHasher`, stable across builds.
+fn fnv1a(parts: &[&str]) -> u64 {
+    let mut hash = FNV_OFFSET;
+
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    hash
+}
@@ -0,0 +1,220 @@
+//! Syntax-tree scan of source files, shared by the code chunker.
+
+use std::str::FromStr;
+
+use tree_sitter::{Node, Parser};
+use tree_sitter_language::LanguageFn;
+
+/// Errors returned for source files.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("unsupported language: {0}")]
+    UnsupportedLanguage(String),
+    #[error("failed to load grammar: {0}")]
+    Grammar(String),
+}
+
+impl Error {
+    /// Short machine-readable name of the error, e.g. `"unsupported_language"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::UnsupportedLanguage(_) => "unsupported_language",
+            Error::Grammar(_) => "grammar",
+        }
+    }
+}
+
+/// Languages `chunk_code` understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    TypeScript,
+    Tsx,
+    Python,
+    Go,
+    Elixir,
+}
+
+impl FromStr for Language {
+    type Err = Error;
+
+    /// Accepts language names and common file extensions, e.g. `"rust"` or `"rs"`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Ok(Self::Rust),
+            "typescript" | "ts" | "javascript" | "js" => Ok(Self::TypeScript),
+            "tsx" | "jsx" => Ok(Self::Tsx),
+            "python" | "py" => Ok(Self::Python),
+            "go" | "golang" => Ok(Self::Go),
+            "elixir" | "ex" | "exs" => Ok(Self::Elixir),
+            _ => Err(Error::UnsupportedLanguage(name.to_string())),
+        }
+    }
+}
+
+impl Language {
+    /// The tree-sitter grammar for this language.
+    pub fn grammar(self) -> tree_sitter::Language {
+        let language = match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE,
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX,
+            Self::Python => tree_sitter_python::LANGUAGE,
+            Self::Go => tree_sitter_go::LANGUAGE,
+            Self::Elixir => tree_sitter_elixir::LANGUAGE,
+        };
+
+        from_language_fn(language)
+    }
+
+    /// Node kinds that define a named symbol.
+    fn definitions(self) -> &'static [&'static str] {
+        match self {
+            Self::Rust => &[
+                "function_item",
+                "function_signature_item",
+                "impl_item",
+                "trait_item",
+                "struct_item",
+                "enum_item",
+                "mod_item",
+                "macro_definition",
+            ],
+            Self::TypeScript | Self::Tsx => &[
+                "function_declaration",
+                "generator_function_declaration",
+                "class_declaration",
+                "abstract_class_declaration",
+                "method_definition",
+                "interface_declaration",
+                "enum_declaration",
+                "internal_module",
+            ],
+            Self::Python => &["function_definition", "class_definition"],
+            Self::Go => &["function_declaration", "method_declaration", "type_spec"],
+            // Every `def`-style macro is a plain call in the Elixir grammar,
+            // see `elixir_name`.
+            Self::Elixir => &["call"],
+        }
+    }
+}
+
+/// A named definition and the byte range it spans in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Collects every function, class, impl, module, ... in `content`, outer
+/// definitions before the ones nested in them.
+pub fn symbols(content: &str, language: Language) -> Vec<Symbol> {
+    let mut parser = Parser::new();
+    if parser.set_language(&language.grammar()).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(content, None) else {
+        return Vec::new();
+    };
+
+    let mut symbols = Vec::new();
+    let mut cursor = tree.walk();
+    let mut stack = vec![tree.root_node()];
+
+    while let Some(node) = stack.pop() {
+        if language.definitions().contains(&node.kind()) {
+            if let Some(name) = symbol_name(node, content, language) {
+                symbols.push(Symbol {
+                    name,
+                    start: node.start_byte(),
+                    end: node.end_byte(),
+                });
+            }
+        }
+
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+
+    symbols
+}
+
+/// Names of the symbols enclosing `offset`, outermost first.
+///
+/// A symbol that starts exactly at `offset` is included, so a chunk that
+/// opens with `fn parse` reports `parse` as its innermost symbol.
+pub fn symbol_path(symbols: &[Symbol], offset: usize) -> Vec<String> {
+    symbols
+        .iter()
+        .filter(|symbol| symbol.start <= offset && offset < symbol.end)
+        .map(|symbol| symbol.name.clone())
+        .collect()
+}
+
+fn symbol_name(node: Node, content: &str, language: Language) -> Option<String> {
+    let text = |node: Node| content[node.byte_range()].to_string();
+
+    match (language, node.kind()) {
+        (Language::Elixir, _) => elixir_name(node, content),
+        (Language::Rust, "impl_item") => {
+            let target = text(node.child_by_field_name("type")?);
+            Some(match node.child_by_field_name("trait") {
+                Some(r#trait) => format!("impl {} for {target}", text(r#trait)),
+                None => format!("impl {target}"),
+            })
+        }
+        _ => node.child_by_field_name("name").map(text),
+    }
+}
+
+/// `defmodule Foo.Bar`, `def run(opts)`, `defp run(opts) when ...` and
+/// friends are all calls; the name is the module alias or the function head.
+fn elixir_name(node: Node, content: &str) -> Option<String> {
+    const DEFINITIONS: &[&str] = &[
+        "defmodule",
+        "defprotocol",
+        "defimpl",
+        "def",
+        "defp",
+        "defmacro",
+        "defmacrop",
+        "defguard",
+        "defguardp",
+        "defdelegate",
+    ];
+
+    let target = node.child_by_field_name("target")?;
+    if !DEFINITIONS.contains(&&content[target.byte_range()]) {
+        return None;
+    }
+
+    let mut cursor = node.walk();
+    let mut head = node
+        .children(&mut cursor)
+        .find(|child| child.kind() == "arguments")?
+        .named_child(0)?;
+
+    // `def run(opts) when is_list(opts)`
+    if head.kind() == "binary_operator" {
+        head = head.child_by_field_name("left")?;
+    }
+    // `def run(opts)`, as opposed to `def run` or `defmodule Foo`
+    if head.kind() == "call" {
+        head = head.child_by_field_name("target")?;
+    }
+
+    Some(content[head.byte_range()].to_string())
+}
+
+/// The grammar crates target a newer tree-sitter than text-splitter links,
+/// but expose the same C `TSLanguage` pointer, so bridge through that.
+fn from_language_fn(language: LanguageFn) -> tree_sitter::Language {
+    // SAFETY: the grammar function returns a pointer to a static
+    // `TSLanguage`, which is exactly what `from_raw` expects. The ABI version
+    // is checked by tree-sitter when a parser is set to the language.
+    unsafe {
+        let raw = (language.into_raw())() as *const tree_sitter::ffi::TSLanguage;
+        tree_sitter::Language::from_raw(raw)
+    }
+}
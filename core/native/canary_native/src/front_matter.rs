@@ -0,0 +1,165 @@
+//! YAML (`---`) and TOML (`+++`) front matter at the top of markdown files,
+//! as written for Docusaurus, Hugo, Jekyll and Astro.
+
+use serde_yaml::{Mapping, Value};
+
+/// The front matter fields we index. Everything else is ignored, and so are
+/// known fields of a type we cannot read.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrontMatter {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// Docusaurus allows fractional positions, e.g. `2.5`.
+    pub sidebar_position: Option<f64>,
+    pub slug: Option<String>,
+    pub draft: bool,
+}
+
+impl FrontMatter {
+    fn from_mapping(mapping: &Mapping) -> Self {
+        Self {
+            title: mapping.get("title").and_then(scalar),
+            description: mapping.get("description").and_then(scalar),
+            tags: mapping.get("tags").map(tags).unwrap_or_default(),
+            sidebar_position: mapping.get("sidebar_position").and_then(number),
+            slug: mapping.get("slug").and_then(scalar),
+            draft: mapping.get("draft").and_then(boolean).unwrap_or_default(),
+        }
+    }
+}
+
+/// A markdown file split into its front matter and body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    /// All defaults when the file has no front matter.
+    pub front_matter: FrontMatter,
+    pub body: String,
+    /// Byte offset of `body` in the original file.
+    pub body_start: usize,
+}
+
+/// Separates the front matter from the body of `content`. A file without a
+/// closed front matter block, or whose block is not a YAML or TOML mapping
+/// (e.g. a `---` thematic break), is all body.
+pub fn parse(content: &str) -> Document {
+    let all_body = || Document {
+        front_matter: FrontMatter::default(),
+        body: content.to_string(),
+        body_start: 0,
+    };
+    let Some((format, raw, end)) = block(content) else {
+        return all_body();
+    };
+
+    let front_matter = if raw.trim().is_empty() {
+        FrontMatter::default()
+    } else {
+        let value = match format {
+            Format::Yaml => serde_yaml::from_str::<Value>(raw).ok(),
+            Format::Toml => raw
+                .parse::<toml::Table>()
+                .ok()
+                .and_then(|table| serde_yaml::to_value(table).ok()),
+        };
+        match value {
+            Some(Value::Mapping(mapping)) => FrontMatter::from_mapping(&mapping),
+            // Only comments.
+            Some(Value::Null) => FrontMatter::default(),
+            _ => return all_body(),
+        }
+    };
+
+    let rest = &content[end..];
+    let body = rest.trim_start_matches(['\r', '\n']);
+
+    Document {
+        front_matter,
+        body: body.to_string(),
+        body_start: end + (rest.len() - body.len()),
+    }
+}
+
+enum Format {
+    Yaml,
+    Toml,
+}
+
+/// The front matter format, its raw text between the delimiters and the byte
+/// offset just past the closing delimiter line.
+fn block(content: &str) -> Option<(Format, &str, usize)> {
+    let content_start = if content.starts_with('\u{feff}') {
+        3
+    } else {
+        0
+    };
+    let mut lines = content[content_start..].split_inclusive('\n');
+
+    let opener = lines.next()?;
+    let (format, closers): (_, &[&str]) = match opener.trim_end() {
+        "---" => (Format::Yaml, &["---", "..."]),
+        "+++" => (Format::Toml, &["+++"]),
+        _ => return None,
+    };
+
+    let raw_start = content_start + opener.len();
+    let mut offset = raw_start;
+    for line in lines {
+        if closers.contains(&line.trim_end()) {
+            return Some((format, &content[raw_start..offset], offset + line.len()));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Strings, and numbers or booleans written where a string was meant.
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(string) => Some(string.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(boolean) => Some(boolean.to_string()),
+        _ => None,
+    }
+}
+
+/// Numbers, and numeric strings like `"3"`.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(string) => string.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Booleans, and the YAML 1.1 words like `yes` that YAML 1.2 reads as strings.
+fn boolean(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(boolean) => Some(*boolean),
+        Value::String(string) => match string.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "y" | "on" => Some(true),
+            "false" | "no" | "n" | "off" => Some(false),
+            _ => None,
+        },
+        Value::Number(number) => number.as_f64().map(|number| number != 0.0),
+        _ => None,
+    }
+}
+
+/// Accepts a list of names, Docusaurus-style `{label: ...}` objects, or a
+/// single space-separated string as Jekyll writes them. Other scalars become
+/// strings and anything else is skipped.
+fn tags(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(tags) => tags.split_whitespace().map(String::from).collect(),
+        Value::Sequence(tags) => tags
+            .iter()
+            .filter_map(|tag| match tag {
+                Value::Mapping(tag) => tag.get("label").and_then(scalar),
+                tag => scalar(tag),
+            })
+            .collect(),
+        tag => scalar(tag).into_iter().collect(),
+    }
+}
@@ -1,6 +1,25 @@
+//! Shallow clones of source repositories.
+
 use std::path::Path;
 
-pub fn clone_depth<'a>(repo_url: &'a str, dest_path: &'a str, depth: i32) -> anyhow::Result<()> {
+/// Errors returned while cloning.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to clone repository: {0}")]
+    Clone(#[from] git2::Error),
+}
+
+impl Error {
+    /// Short machine-readable name of the error, e.g. `"clone"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Clone(_) => "clone",
+        }
+    }
+}
+
+/// Clones `repo_url` into `dest_path`, fetching only the last `depth` commits.
+pub fn clone_depth(repo_url: &str, dest_path: &str, depth: i32) -> Result<(), Error> {
     let mut fo = git2::FetchOptions::new();
     fo.depth(depth);
 
@@ -1,13 +1,383 @@
+//! HTML→markdown conversion for crawled pages.
+
+use ego_tree::NodeId;
 use htmd::HtmlToMarkdown;
+use html5ever::{namespace_url, ns, LocalName, QualName};
+use scraper::node::Element;
+use scraper::{ElementRef, Html, Node, Selector};
+use url::Url;
+
+use crate::markdown;
+
+mod admonitions;
+mod code_blocks;
+mod links;
+mod main_content;
+mod meta;
+mod preset;
+
+pub use links::{links, Link, LinkFilter};
+pub use meta::{page_meta, Alternate, PageMeta};
+pub use preset::Preset;
+
+/// Errors returned while converting HTML.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to parse html: {0}")]
+    Parse(#[from] std::io::Error),
+    #[error("invalid selector `{selector}`: {message}")]
+    Selector { selector: String, message: String },
+    #[error("unknown preset `{0}`")]
+    Preset(String),
+    #[error("invalid url `{url}`: {source}")]
+    Url {
+        url: String,
+        source: url::ParseError,
+    },
+}
+
+impl Error {
+    /// Short machine-readable name of the error, e.g. `"parse"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Parse(_) => "parse",
+            Error::Selector { .. } => "selector",
+            Error::Preset(_) => "preset",
+            Error::Url { .. } => "url",
+        }
+    }
+}
+
+/// Boilerplate of common documentation themes, dropped when no `exclude`
+/// selectors are configured.
+pub const DEFAULT_EXCLUDE: &[&str] = &[
+    ".theme-doc-toc-mobile",
+    ".theme-doc-toc-desktop",
+    ".theme-doc-breadcrumbs",
+    ".theme-doc-footer",
+    ".pagination-nav",
+    ".VPLocalNav",
+    ".VPDocFooter",
+    ".VPDocAsideOutline",
+];
+
+/// What to keep of a page before converting it.
+///
+/// The kept content is the `include` matches when there are any selectors,
+/// else the preset's content root when the page has one, else the
+/// `main_content` extraction when enabled, else the whole page.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Options {
+    /// Keep only the primary content, found by scoring elements on text and
+    /// link density, `<main>`/`<article>` and ARIA roles. Sidebars, cookie
+    /// banners, pagination and tables of contents are dropped. Unused when
+    /// `include` or the preset's content root applies.
+    pub main_content: bool,
+    /// CSS selectors of the elements to keep, e.g. `article.markdown`. When
+    /// set, takes the place of `main_content` and the preset's content root.
+    pub include: Vec<String>,
+    /// CSS selectors of the elements to drop, e.g. `.pagination-nav`.
+    /// [`DEFAULT_EXCLUDE`] when empty.
+    pub exclude: Vec<String>,
+    /// Address of the page. Relative links and image sources are resolved
+    /// against it, or against `<base href>` when the page has one.
+    pub url: Option<String>,
+    /// Turn links to other origins than the page's into plain text.
+    pub drop_external_links: bool,
+    /// Framework whose content root, chrome and tabs to handle. Detected
+    /// from the page when unset. Its content root takes the place of
+    /// `main_content`, but not of `include`.
+    pub preset: Option<Preset>,
+}
+
+/// A page's metadata along with its sections, from a single parse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub meta: PageMeta,
+    pub sections: Vec<Section>,
+}
+
+/// A heading and the markdown under it, up to the next heading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    /// The heading's `id`, used as the URL fragment of the section.
+    pub id: Option<String>,
+    pub level: usize,
+    pub title: String,
+    /// The heading line followed by the section's markdown.
+    pub content: String,
+}
+
+/// Converts an HTML document to markdown, skipping scripts, styles and
+/// navigation chrome.
+pub fn to_md(content: &str, opts: &Options) -> Result<String, Error> {
+    convert(&select(Html::parse_document(content), opts)?, false)
+}
+
+/// Converts an HTML document like [`to_md`] and splits the markdown at every
+/// heading. Text before the first heading is dropped.
+pub fn to_sections(content: &str, opts: &Options) -> Result<Vec<Section>, Error> {
+    let md = convert(&select(Html::parse_document(content), opts)?, true)?;
+    Ok(sections(&md))
+}
 
-pub fn to_md<'a>(content: &'a str) -> anyhow::Result<String> {
-    let converter = HtmlToMarkdown::builder()
+/// Reads the metadata of an HTML document like [`page_meta`], against
+/// `opts.url`, and splits it into sections like [`to_sections`].
+pub fn to_page(content: &str, opts: &Options) -> Result<Page, Error> {
+    let document = Html::parse_document(content);
+    let meta = meta::read(&document, opts.url.as_deref())?;
+    let md = convert(&select(document, opts)?, true)?;
+
+    Ok(Page {
+        meta,
+        sections: sections(&md),
+    })
+}
+
+fn sections(md: &str) -> Vec<Section> {
+    let headings = markdown::headings(md);
+    let ends = headings
+        .iter()
+        .skip(1)
+        .map(|heading| heading.start)
+        .chain([md.len()]);
+
+    headings
+        .iter()
+        .zip(ends)
+        .map(|(heading, end)| {
+            let line = format!("{} {}", "#".repeat(heading.level), heading.title);
+            let body = md[heading.start..end]
+                .split_once('\n')
+                .map_or("", |(_, body)| body.trim());
+
+            Section {
+                id: heading.id.clone(),
+                level: heading.level,
+                title: heading.title.clone(),
+                content: if body.is_empty() {
+                    line
+                } else {
+                    format!("{line}\n\n{body}")
+                },
+            }
+        })
+        .collect()
+}
+
+/// Drops excluded elements, resolves links and keeps the included, preset or
+/// main content, returning the HTML to convert.
+fn select(mut document: Html, opts: &Options) -> Result<String, Error> {
+    let preset = opts.preset.or_else(|| Preset::detect(&document));
+
+    let mut excluded = if opts.exclude.is_empty() {
+        selectors(DEFAULT_EXCLUDE)?
+    } else {
+        selectors(&opts.exclude)?
+    };
+    excluded.extend(preset.map(Preset::exclude).unwrap_or_default());
+    let excluded: Vec<NodeId> = excluded
+        .iter()
+        .flat_map(|selector| document.select(selector))
+        .map(|element| element.id())
+        .collect();
+    detach(&mut document, excluded);
+    let preset_content = preset.and_then(|preset| preset.content(&document));
+    keep_article_headers(&mut document, preset_content.as_ref());
+    code_blocks::normalize(&mut document);
+    admonitions::normalize(&mut document);
+    if let Some(preset) = preset {
+        preset.flatten_tabs(&mut document);
+    }
+    if let Some(base) = base_url(&document, opts.url.as_deref())? {
+        resolve_links(&mut document, &base, opts.drop_external_links);
+    }
+
+    Ok(if !opts.include.is_empty() {
+        included(&document, &selectors(&opts.include)?)
+    } else if let Some(content) = preset_content {
+        included(&document, &[content])
+    } else if opts.main_content {
+        main_content::extract(document)
+    } else {
+        document.html()
+    })
+}
+
+/// HTML to markdown. With `heading_ids`, headings keep their `id` as a
+/// `{#id}` attribute for [`markdown::headings`] to read back.
+fn convert(content: &str, heading_ids: bool) -> Result<String, Error> {
+    let mut converter = HtmlToMarkdown::builder()
         .skip_tags(vec!["script", "style", "nav", "header", "footer"])
         .add_handler(vec!["div"], handle_div_aria_label)
         .add_handler(vec!["a"], handle_internal_link)
-        .build();
+        .add_handler(vec!["blockquote"], handle_blockquote);
+    if heading_ids {
+        converter = converter.add_handler(
+            vec!["h1", "h2", "h3", "h4", "h5", "h6"],
+            handle_heading_with_id,
+        );
+    }
+
+    Ok(converter.build().convert(content)?)
+}
+
+/// The page URL, or the `<base href>` resolved against it.
+fn base_url(document: &Html, url: Option<&str>) -> Result<Option<Url>, Error> {
+    let parse = |url: &str| {
+        Url::parse(url).map_err(|source| Error::Url {
+            url: url.to_string(),
+            source,
+        })
+    };
+    let page = url.map(parse).transpose()?;
+
+    let base = document
+        .select(&selector("base[href]"))
+        .next()
+        .and_then(|base| base.value().attr("href"));
 
-    converter.convert(content).map_err(anyhow::Error::from)
+    Ok(match (page, base) {
+        (Some(page), Some(base)) => Some(page.join(base).unwrap_or(page)),
+        (None, Some(base)) => Url::parse(base).ok(),
+        (page, None) => page,
+    })
+}
+
+/// A `<header>` inside `<article>`, `<main>` or the preset's content root
+/// holds the page title rather than site chrome, so it becomes a `<div>` to
+/// survive conversion.
+fn keep_article_headers(document: &mut Html, content: Option<&Selector>) {
+    let ids: Vec<NodeId> = document
+        .root_element()
+        .descendent_elements()
+        .filter(|element| element.value().name() == "header")
+        .filter(|element| {
+            element
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .any(|ancestor| {
+                    matches!(ancestor.value().name(), "article" | "main")
+                        || content.is_some_and(|content| content.matches(&ancestor))
+                })
+        })
+        .map(|element| element.id())
+        .collect();
+
+    for id in ids {
+        if let Some(mut node) = document.tree.get_mut(id) {
+            if let Node::Element(element) = node.value() {
+                element.name.local = "div".into();
+            }
+        }
+    }
+}
+
+/// Makes every link and image source absolute. Links to other origins are
+/// unwrapped when `drop_external` is set, and `javascript:` links always are.
+fn resolve_links(document: &mut Html, base: &Url, drop_external: bool) {
+    let ids: Vec<NodeId> = document
+        .root_element()
+        .descendent_elements()
+        .filter(|element| matches!(element.value().name(), "a" | "img"))
+        .map(|element| element.id())
+        .collect();
+
+    for id in ids {
+        let Some(mut node) = document.tree.get_mut(id) else {
+            continue;
+        };
+        let Node::Element(element) = node.value() else {
+            continue;
+        };
+        let attr = if element.name() == "a" { "href" } else { "src" };
+
+        let Some(value) = element
+            .attrs
+            .iter_mut()
+            .find_map(|(name, value)| (&*name.local == attr).then_some(value))
+        else {
+            continue;
+        };
+        // In-page anchors are dropped during conversion anyway.
+        if value.starts_with('#') {
+            continue;
+        }
+        let Ok(url) = base.join(value.trim()) else {
+            continue;
+        };
+
+        let unwrap = attr == "href"
+            && (url.scheme() == "javascript" || drop_external && url.origin() != base.origin());
+        if unwrap {
+            element.attrs.retain(|name, _| &*name.local != "href");
+        } else {
+            *value = url.as_str().into();
+        }
+    }
+}
+
+fn selectors(selectors: &[impl AsRef<str>]) -> Result<Vec<Selector>, Error> {
+    selectors
+        .iter()
+        .map(|selector| {
+            let selector = selector.as_ref();
+            Selector::parse(selector).map_err(|e| Error::Selector {
+                selector: selector.to_string(),
+                message: e.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses a selector written in the source.
+fn selector(selector: &str) -> Selector {
+    Selector::parse(selector).expect("valid selector")
+}
+
+/// Text of `element` with runs of whitespace collapsed.
+fn text(element: ElementRef) -> String {
+    element
+        .text()
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// An empty HTML element, e.g. for `local_name!("p")`.
+fn element_node(name: LocalName) -> Node {
+    Node::Element(Element::new(
+        QualName::new(None, ns!(html), name),
+        Vec::new(),
+    ))
+}
+
+/// Removes the nodes `ids` and everything under them from the tree.
+fn detach(html: &mut Html, ids: Vec<NodeId>) {
+    for id in ids {
+        if let Some(mut node) = html.tree.get_mut(id) {
+            node.detach();
+        }
+    }
+}
+
+/// Outer HTML of every element matching one of `selectors`, in document
+/// order. Matches nested in another match are only kept once.
+fn included(document: &Html, selectors: &[Selector]) -> String {
+    let matches = |element: &ElementRef| selectors.iter().any(|selector| selector.matches(element));
+
+    document
+        .root_element()
+        .descendent_elements()
+        .filter(|element| matches(element))
+        .filter(|element| {
+            !element
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .any(|ancestor| matches(&ancestor))
+        })
+        .map(|element| element.html())
+        .collect()
 }
 
 fn handle_div_aria_label(element: htmd::Element) -> Option<String> {
@@ -24,6 +394,55 @@ fn handle_div_aria_label(element: htmd::Element) -> Option<String> {
     Some(element.content.to_string())
 }
 
+/// htmd's blockquote, opened with `[!TIP]` for the `data-alert` of
+/// admonitions. Blank lines inside are a bare `>`.
+fn handle_blockquote(element: htmd::Element) -> Option<String> {
+    let alert = element
+        .attrs
+        .iter()
+        .find(|attr| attr.name.local.as_ref() == "data-alert")
+        .map(|attr| format!("[!{}]\n", attr.value));
+    let content = element.content.trim_start_matches('\n').trim_end();
+    let quoted = alert
+        .as_deref()
+        .unwrap_or_default()
+        .lines()
+        .chain(content.lines())
+        .map(|line| format!("> {line}").trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some(format!("\n\n{quoted}\n\n"))
+}
+
+fn handle_heading_with_id(element: htmd::Element) -> Option<String> {
+    let level = element.tag[1..].parse::<usize>().ok()?;
+    // Docusaurus puts a zero-width space in its hash links.
+    let title = element
+        .content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches('\u{200b}')
+        .trim()
+        .to_string();
+    if title.is_empty() {
+        return Some(String::new());
+    }
+
+    let id = element
+        .attrs
+        .iter()
+        .find(|attr| attr.name.local.as_ref() == "id")
+        .map(|attr| attr.value.trim())
+        .filter(|id| !id.is_empty() && !id.contains(|c: char| c.is_whitespace() || c == '}'));
+
+    Some(match id {
+        Some(id) => format!("\n\n{} {title} {{#{id}}}\n\n", "#".repeat(level)),
+        None => format!("\n\n{} {title}\n\n", "#".repeat(level)),
+    })
+}
+
 fn handle_internal_link(element: htmd::Element) -> Option<String> {
     if let Some(href) = element
         .attrs
@@ -0,0 +1,242 @@
+//! Admonitions and callouts as Docusaurus, VitePress, MkDocs, Sphinx, GitBook,
+//! Nextra, Mintlify and GitHub render them. Each becomes a `<blockquote>` holding its
+//! body: kinds GitHub has an alert for get a `data-alert` the blockquote
+//! handler writes as `[!TIP]`, other kinds get a bold `Title:` paragraph.
+
+use ego_tree::NodeId;
+use html5ever::{local_name, namespace_url, ns, Attribute, LocalName, QualName};
+use scraper::node::{Element, Text};
+use scraper::{ElementRef, Html, Node};
+
+use super::{detach, element_node, text};
+
+/// Classes only callout containers have.
+const CALLOUTS: &[&str] = &[
+    "markdown-alert",
+    "theme-admonition",
+    "custom-block",
+    "nextra-callout",
+];
+
+/// Elements a callout can be; an inline `<span class="hint">` is not one.
+const CONTAINERS: &[&str] = &["div", "aside", "section", "details", "blockquote"];
+
+/// Classes of callout titles, e.g. MkDocs' `<p class="admonition-title">`.
+const TITLES: &[&str] = &[
+    "markdown-alert-title",
+    "admonition-title",
+    "admonition-heading",
+    "custom-block-title",
+];
+
+/// Class prefixes in front of the kind, e.g. `theme-admonition-tip`.
+const KIND_PREFIXES: &[&str] = &[
+    "theme-admonition-",
+    "markdown-alert-",
+    "admonition-",
+    "hint-",
+];
+
+/// Kinds and the GitHub alert they read as.
+const ALERTS: &[(&str, &str)] = &[
+    ("note", "NOTE"),
+    ("info", "NOTE"),
+    ("tip", "TIP"),
+    ("hint", "TIP"),
+    ("important", "IMPORTANT"),
+    ("warning", "WARNING"),
+    ("attention", "WARNING"),
+    ("caution", "CAUTION"),
+    ("danger", "CAUTION"),
+    ("error", "CAUTION"),
+];
+
+/// Kinds without a GitHub alert, written as a bold title instead.
+const KINDS: &[&str] = &[
+    "abstract", "summary", "tldr", "success", "check", "done", "question", "help", "faq",
+    "failure", "fail", "missing", "bug", "example", "quote", "cite", "seealso", "todo", "details",
+];
+
+/// Rewrites every admonition and callout of `html` into a blockquote.
+pub fn normalize(html: &mut Html) {
+    let callouts: Vec<NodeId> = html
+        .root_element()
+        .descendent_elements()
+        .filter(|element| is_callout(*element))
+        .map(|element| element.id())
+        .collect();
+
+    for callout in callouts {
+        normalize_callout(html, callout);
+    }
+}
+
+fn normalize_callout(html: &mut Html, id: NodeId) {
+    let Some(element) = html.tree.get(id).and_then(ElementRef::wrap) else {
+        return;
+    };
+    let title_element = element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .find(|child| is_title(*child));
+    let title = title_element
+        .map(text)
+        .map(|title| title.trim_end_matches(':').trim().to_string())
+        .filter(|title| !title.is_empty());
+    let kind = kind_of(element);
+    // Icons: Mintlify's `<svg>` wrapper and Nextra's emoji.
+    let icons = element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|child| is_icon(*child))
+        .map(|child| child.id());
+    let chrome: Vec<NodeId> = title_element
+        .map(|title| title.id())
+        .into_iter()
+        .chain(icons)
+        .collect();
+
+    let alert = kind
+        .as_deref()
+        .and_then(|kind| ALERTS.iter().find(|(name, _)| *name == kind))
+        .map(|(_, alert)| *alert);
+    let label = match (alert, title) {
+        // Only custom titles, e.g. Docusaurus' `:::tip Pro tip`.
+        (Some(alert), Some(title)) => (!title.eq_ignore_ascii_case(alert)
+            && !kind
+                .as_deref()
+                .is_some_and(|kind| title.eq_ignore_ascii_case(kind)))
+        .then_some(title),
+        (Some(_), None) => None,
+        (None, Some(title)) => Some(format!("{title}:")),
+        (None, None) => kind.as_deref().map(|kind| format!("{}:", capitalize(kind))),
+    };
+
+    detach(html, chrome);
+
+    let mut attrs = Vec::new();
+    if let Some(alert) = alert {
+        attrs.push(Attribute {
+            name: QualName::new(None, ns!(), LocalName::from("data-alert")),
+            value: alert.into(),
+        });
+    }
+    let Some(mut node) = html.tree.get_mut(id) else {
+        return;
+    };
+    *node.value() = Node::Element(Element::new(
+        QualName::new(None, ns!(html), local_name!("blockquote")),
+        attrs,
+    ));
+    if let Some(label) = label {
+        node.prepend(element_node(local_name!("p")))
+            .append(element_node(local_name!("strong")))
+            .append(Node::Text(Text { text: label.into() }));
+    }
+}
+
+/// A block container with a framework's callout class. The generic words
+/// count only with their framework's markup around them: MkDocs' and
+/// Sphinx' `admonition` with a title, Mintlify's `callout` with
+/// `data-callout-type` and GitBook's `hint` with a `hint-*` kind.
+fn is_callout(element: ElementRef) -> bool {
+    let value = element.value();
+    if !CONTAINERS.contains(&value.name()) {
+        return false;
+    }
+    let has_class = |name: &str| value.classes().any(|class| class == name);
+
+    value.classes().any(|class| CALLOUTS.contains(&class))
+        || (has_class("admonition")
+            && element
+                .children()
+                .filter_map(ElementRef::wrap)
+                .any(is_title))
+        || (has_class("callout") && value.attr("data-callout-type").is_some())
+        || (has_class("hint") && value.classes().any(|class| class.starts_with("hint-")))
+}
+
+fn is_title(element: ElementRef) -> bool {
+    element.value().name() == "summary"
+        || element
+            .value()
+            .classes()
+            .any(|class| TITLES.contains(&class) || class.starts_with("admonitionHeading"))
+}
+
+/// Children with an icon and no other text.
+fn is_icon(element: ElementRef) -> bool {
+    let text = text(element);
+    if text.is_empty() {
+        return element
+            .descendent_elements()
+            .any(|child| child.value().name() == "svg")
+            && !element
+                .descendent_elements()
+                .any(|child| child.value().name() == "img");
+    }
+
+    emoji_kind(&text).is_some()
+}
+
+/// The kind from a class like `tip` or `admonition-tip`, Mintlify's
+/// `data-callout-type` or icon label, or Nextra's emoji.
+fn kind_of(element: ElementRef) -> Option<String> {
+    let value = element.value();
+    // `hint-warning` wins over GitBook's bare `hint`.
+    let prefixed = value.classes().find_map(|class| {
+        KIND_PREFIXES
+            .iter()
+            .find_map(|prefix| class.strip_prefix(prefix))
+            .map(str::to_ascii_lowercase)
+            .filter(|kind| is_kind(kind))
+    });
+    let from_class = prefixed.or_else(|| {
+        value
+            .classes()
+            .map(str::to_ascii_lowercase)
+            .find(|kind| is_kind(kind))
+    });
+
+    from_class
+        .or_else(|| value.attr("data-callout-type").map(str::to_ascii_lowercase))
+        .or_else(|| {
+            element
+                .descendent_elements()
+                .filter(|child| child.value().name() == "svg")
+                .find_map(|svg| svg.value().attr("aria-label"))
+                .map(str::to_ascii_lowercase)
+        })
+        .or_else(|| {
+            element
+                .children()
+                .filter_map(ElementRef::wrap)
+                .find_map(|child| emoji_kind(&text(child)))
+                .map(str::to_string)
+        })
+        .map(|kind| kind.trim().to_string())
+        .filter(|kind| is_kind(kind))
+}
+
+fn is_kind(kind: &str) -> bool {
+    ALERTS.iter().any(|(name, _)| *name == kind) || KINDS.contains(&kind)
+}
+
+/// Nextra's callout emoji.
+fn emoji_kind(text: &str) -> Option<&'static str> {
+    match text.trim_end_matches('\u{fe0f}') {
+        "💡" => Some("tip"),
+        "ℹ" => Some("info"),
+        "⚠" => Some("warning"),
+        "🚫" => Some("error"),
+        _ => None,
+    }
+}
+
+fn capitalize(kind: &str) -> String {
+    let mut chars = kind.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
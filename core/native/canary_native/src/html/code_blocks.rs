@@ -0,0 +1,287 @@
+//! Code blocks as Prism, Shiki, highlight.js and Pygments render them. Each
+//! becomes a plain `<pre><code class="language-*">`, which htmd writes as a
+//! fence with the language as its info string. Diff lines get `+`/`-`
+//! prefixes, and line numbers and copy buttons are dropped.
+
+use ego_tree::NodeId;
+use html5ever::{local_name, namespace_url, ns, Attribute, QualName};
+use scraper::node::{Element, Text};
+use scraper::{ElementRef, Html, Node};
+
+use super::detach;
+
+/// Class fragments of line-number gutters, e.g. Prism's `line-numbers-rows`,
+/// Pygments' `linenos` and highlight.js' `hljs-ln-numbers`.
+const GUTTERS: &[&str] = &[
+    "line-numbers-rows",
+    "linenos",
+    "lineno",
+    "linenumber",
+    "line-number",
+    "hljs-ln-n",
+    "gutter",
+];
+
+/// Class prefixes that name the language, longest first.
+const LANGUAGE_PREFIXES: &[&str] = &["highlight-source-", "highlight-", "language-", "lang-"];
+
+/// Class prefixes that name the language on a highlighter's wrapper, e.g.
+/// Docusaurus' `language-js` or GitHub's `highlight-source-rust`. Sphinx'
+/// `highlight-*` counts only around its `div.highlight`.
+const WRAPPER_PREFIXES: &[&str] = &["highlight-source-", "language-"];
+
+/// How far above `<pre>` to look for the language and the toolbar; Sphinx
+/// puts `highlight-python` six levels up when line numbers are on.
+const MAX_CONTAINER_DEPTH: usize = 6;
+
+/// Class fragments of code block toolbars, e.g. Docusaurus' `buttonGroup`.
+const TOOLBAR: &[&str] = &["copy", "toolbar", "buttongroup"];
+
+/// Longest text of a button group, e.g. a "Copied!" tooltip.
+const MAX_TOOLTIP_CHARS: usize = 20;
+
+/// Elements that make an ancestor page content rather than a code block
+/// wrapper.
+const PROSE: &[&str] = &[
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ul",
+    "ol",
+    "li",
+    "dl",
+    "blockquote",
+    "pre",
+];
+
+/// Rewrites every `<pre>` of `html` for conversion.
+pub fn normalize(html: &mut Html) {
+    let gutters = collect(html, is_gutter);
+    detach(html, gutters);
+
+    for pre in collect(html, |element| element.value().name() == "pre") {
+        normalize_block(html, pre);
+    }
+}
+
+fn normalize_block(html: &mut Html, pre: NodeId) {
+    let Some(element) = html.tree.get(pre).and_then(ElementRef::wrap) else {
+        return;
+    };
+    let code = element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .find(|child| child.value().name() == "code");
+    // Only wrappers holding nothing but this code block; i18n markup like
+    // `<html data-lang="en">` or `class="lang-en"` must not name it.
+    let container = element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .take(MAX_CONTAINER_DEPTH)
+        .take_while(|ancestor| is_wrapper(*ancestor, element))
+        .find(|ancestor| wrapper_language(*ancestor).is_some());
+    let language = code
+        .into_iter()
+        .chain([element])
+        .find_map(language_of)
+        .or_else(|| container.and_then(wrapper_language));
+
+    // Copy buttons, tooltips and VitePress' language label next to the
+    // `<pre>`, inside its wrapper only.
+    let toolbar: Vec<NodeId> = container
+        .unwrap_or(element)
+        .descendent_elements()
+        .filter(|child| {
+            !child.descendent_elements().any(|inner| inner == element)
+                && !child.ancestors().any(|parent| parent == *element)
+                && is_toolbar(*child)
+        })
+        .map(|child| child.id())
+        .collect();
+    let text = code_text(code.unwrap_or(element));
+    let children: Vec<NodeId> = element.children().map(|child| child.id()).collect();
+
+    detach(html, toolbar);
+    detach(html, children);
+
+    // Highlighters wrap every token in a span; htmd only needs the text, in
+    // a `<pre><code>` carrying the language class.
+    let mut attrs = Vec::new();
+    if let Some(language) = language {
+        attrs.push(Attribute {
+            name: QualName::new(None, ns!(), local_name!("class")),
+            value: format!("language-{language}").into(),
+        });
+    }
+    if let Some(mut pre) = html.tree.get_mut(pre) {
+        // htmd falls back to the `<pre>` classes, e.g. Prism's `language-none`.
+        if let Node::Element(pre) = pre.value() {
+            pre.attrs.retain(|name, _| &*name.local != "class");
+        }
+        pre.append(Node::Element(Element::new(
+            QualName::new(None, ns!(html), local_name!("code")),
+            attrs,
+        )))
+        .append(Node::Text(Text { text: text.into() }));
+    }
+}
+
+/// Text of a code block with `<br>` and table rows (highlight.js line
+/// numbers) as line breaks, and diff lines prefixed.
+fn code_text(code: ElementRef) -> String {
+    let mut text = String::new();
+
+    for node in code.descendants() {
+        match node.value() {
+            Node::Text(t) => text.push_str(t),
+            Node::Element(element) if element.name() == "br" => text.push('\n'),
+            Node::Element(element)
+                if element.name() == "tr" && !text.is_empty() && !text.ends_with('\n') =>
+            {
+                text.push('\n')
+            }
+            Node::Element(_) => {
+                if let Some(marker) = ElementRef::wrap(node).and_then(diff_marker) {
+                    text.push_str(marker);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    text
+}
+
+/// The language a `<pre>` or `<code>` names by `data-language`, `data-lang`,
+/// Mintlify's `language` or a class like `language-python`.
+fn language_of(element: ElementRef) -> Option<String> {
+    let value = element.value();
+    let language = value
+        .attr("data-language")
+        .or_else(|| value.attr("data-lang"))
+        .or_else(|| value.attr("language"))
+        .map(str::to_string)
+        .or_else(|| {
+            value.classes().find_map(|class| {
+                LANGUAGE_PREFIXES
+                    .iter()
+                    .find_map(|prefix| class.strip_prefix(prefix))
+                    .map(str::to_string)
+            })
+        })?
+        .trim()
+        .to_ascii_lowercase();
+
+    (!language.is_empty() && language != "none").then_some(language)
+}
+
+/// The language of a highlighter's wrapper, from Mintlify's `language` or a
+/// class like `language-js`, `highlight-source-rust` or Sphinx'
+/// `highlight-python` around a `div.highlight`.
+fn wrapper_language(wrapper: ElementRef) -> Option<String> {
+    let value = wrapper.value();
+    let sphinx = wrapper
+        .descendent_elements()
+        .any(|child| has_class(child.value(), "highlight"));
+    let language = value
+        .attr("language")
+        .map(str::to_string)
+        .or_else(|| {
+            value.classes().find_map(|class| {
+                WRAPPER_PREFIXES
+                    .iter()
+                    .find_map(|prefix| class.strip_prefix(prefix))
+                    .or_else(|| class.strip_prefix("highlight-").filter(|_| sphinx))
+                    .map(str::to_string)
+            })
+        })?
+        .trim()
+        .to_ascii_lowercase();
+
+    (!language.is_empty() && language != "none").then_some(language)
+}
+
+/// Buttons, elements with a toolbar class, and groups of a button and a
+/// short tooltip like Mintlify's "Copy".
+fn is_toolbar(element: ElementRef) -> bool {
+    let value = element.value();
+    let toolbar_class = value.classes().any(|class| {
+        let class = class.to_ascii_lowercase();
+        class == "lang" || TOOLBAR.iter().any(|toolbar| class.contains(toolbar))
+    });
+    let button_group = element
+        .descendent_elements()
+        .any(|child| child.value().name() == "button")
+        && element
+            .text()
+            .map(str::trim)
+            .collect::<String>()
+            .chars()
+            .count()
+            <= MAX_TOOLTIP_CHARS;
+
+    value.name() == "button" || toolbar_class || button_group
+}
+
+/// Whether `ancestor` holds nothing but the code block `pre` and its toolbar.
+fn is_wrapper(ancestor: ElementRef, pre: ElementRef) -> bool {
+    ancestor.descendent_elements().all(|child| {
+        child == pre
+            || child.ancestors().any(|parent| parent == *pre)
+            || !PROSE.contains(&child.value().name())
+    })
+}
+
+/// `+` or `-` for lines marked `diff add` or `diff remove`.
+fn diff_marker(line: ElementRef) -> Option<&'static str> {
+    let value = line.value();
+    if !has_class(value, "diff") {
+        return None;
+    }
+
+    if has_class(value, "add") {
+        Some("+")
+    } else if has_class(value, "remove") {
+        Some("-")
+    } else {
+        None
+    }
+}
+
+/// Line-number gutters inside a `<pre>`, or wrapping one as Pygments'
+/// `<td class="linenos">` does.
+fn is_gutter(element: ElementRef) -> bool {
+    let value = element.value();
+    if matches!(value.name(), "pre" | "code") {
+        return false;
+    }
+    let is_gutter = value.classes().any(|class| {
+        let class = class.to_ascii_lowercase();
+        GUTTERS.iter().any(|gutter| class.contains(gutter))
+    });
+
+    is_gutter
+        && (element
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|ancestor| ancestor.value().name() == "pre")
+            || element
+                .descendent_elements()
+                .any(|child| child.value().name() == "pre"))
+}
+
+fn has_class(element: &Element, class: &str) -> bool {
+    element.classes().any(|name| name == class)
+}
+
+fn collect(html: &Html, filter: impl Fn(ElementRef) -> bool) -> Vec<NodeId> {
+    html.root_element()
+        .descendent_elements()
+        .filter(|element| filter(*element))
+        .map(|element| element.id())
+        .collect()
+}
@@ -0,0 +1,160 @@
+//! Outbound links of a page, resolved and filtered for the crawler frontier.
+
+use std::collections::HashMap;
+
+use scraper::{ElementRef, Html};
+use url::Url;
+
+use super::{base_url, selector, text, Error};
+
+/// A link found on a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    /// Absolute URL without the fragment.
+    pub url: String,
+    /// Anchor text, or the `aria-label` or image `alt` of icon links.
+    pub text: String,
+    /// Lowercased `rel` values, e.g. `["nofollow"]` or `["next"]`.
+    pub rel: Vec<String>,
+    /// Whether the link stays on the scheme, host and port of the page's
+    /// `url`, whatever `<base href>` says; `false` when `url` is not known.
+    pub same_origin: bool,
+}
+
+impl Link {
+    /// Whether the page asks crawlers not to follow the link.
+    pub fn nofollow(&self) -> bool {
+        self.rel.iter().any(|rel| rel == "nofollow")
+    }
+}
+
+/// Glob patterns links are matched against, as configured for a webpage
+/// source. Links are matched without their query, the way the crawler
+/// records page URLs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkFilter {
+    /// Keep only links matching one of these; all links when empty.
+    pub include: Vec<String>,
+    /// Drop links matching any of these, even when included.
+    pub exclude: Vec<String>,
+}
+
+impl LinkFilter {
+    /// Whether `url` passes the include and exclude patterns.
+    pub fn matches(&self, url: &str) -> bool {
+        !self
+            .exclude
+            .iter()
+            .any(|pattern| crate::glob_match(pattern, url))
+            && (self.include.is_empty()
+                || self
+                    .include
+                    .iter()
+                    .any(|pattern| crate::glob_match(pattern, url)))
+    }
+}
+
+/// Collects the http(s) links of `content` from `<a>`, `<area>` and the
+/// `canonical`, `next` and `prev` `<link>` elements, in document order.
+///
+/// Links are resolved against `url` and `<base href>`; relative links are
+/// skipped when neither is known. Each URL is returned once, with the first
+/// non-empty text and the `rel` values of all its occurrences.
+pub fn links(content: &str, url: &str, filter: &LinkFilter) -> Result<Vec<Link>, Error> {
+    let document = Html::parse_document(content);
+    let page = Some(url).filter(|url| !url.is_empty());
+    let base = base_url(&document, page)?;
+    // `base_url` has already rejected a malformed `url`.
+    let origin = page
+        .and_then(|page| Url::parse(page).ok())
+        .map(|page| page.origin());
+    let links_selector = selector("a[href], area[href], link[href][rel]");
+
+    let mut links: Vec<Link> = Vec::new();
+    // Index into `links` of each URL seen so far.
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for element in document.select(&links_selector) {
+        let rel: Vec<String> = element
+            .value()
+            .attr("rel")
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_ascii_lowercase)
+            .collect();
+        if element.value().name() == "link"
+            && !rel
+                .iter()
+                .any(|rel| matches!(rel.as_str(), "canonical" | "next" | "prev"))
+        {
+            continue;
+        }
+
+        let href = element.value().attr("href").unwrap_or_default().trim();
+        let Some(mut resolved) = resolve(base.as_ref(), href) else {
+            continue;
+        };
+        if !matches!(resolved.scheme(), "http" | "https") {
+            continue;
+        }
+        resolved.set_fragment(None);
+        let mut page_url = resolved.clone();
+        page_url.set_query(None);
+        if !filter.matches(page_url.as_str()) {
+            continue;
+        }
+
+        if let Some(&index) = seen.get(resolved.as_str()) {
+            let link = &mut links[index];
+            if link.text.is_empty() {
+                link.text = label(element);
+            }
+            for rel in rel {
+                if !link.rel.contains(&rel) {
+                    link.rel.push(rel);
+                }
+            }
+            continue;
+        }
+
+        seen.insert(resolved.to_string(), links.len());
+        links.push(Link {
+            same_origin: origin
+                .as_ref()
+                .is_some_and(|origin| *origin == resolved.origin()),
+            url: resolved.into(),
+            text: label(element),
+            rel,
+        });
+    }
+
+    Ok(links)
+}
+
+fn resolve(base: Option<&Url>, href: &str) -> Option<Url> {
+    match base {
+        Some(base) => base.join(href).ok(),
+        None => Url::parse(href).ok(),
+    }
+}
+
+/// Anchor text, or the label of an icon link.
+fn label(element: ElementRef) -> String {
+    let text = text(element);
+    if !text.is_empty() {
+        return text;
+    }
+
+    element
+        .value()
+        .attr("aria-label")
+        .or_else(|| element.value().attr("title"))
+        .or_else(|| {
+            element
+                .select(&selector("img[alt]"))
+                .next()
+                .and_then(|img| img.value().attr("alt"))
+        })
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
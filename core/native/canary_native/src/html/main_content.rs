@@ -0,0 +1,231 @@
+//! Readability-style main content detection: keeps the element holding a
+//! page's primary text and drops the chrome around and inside it. Inside
+//! that element only unambiguous chrome goes, so a `share` or `toc` class on
+//! part of an article does not cost it that part.
+
+use std::collections::HashMap;
+
+use ego_tree::NodeId;
+use scraper::{ElementRef, Html};
+
+use super::{detach, text};
+
+/// Tags that are never primary content.
+const CHROME_TAGS: &[&str] = &[
+    "nav", "aside", "header", "footer", "form", "button", "dialog", "noscript", "template",
+];
+
+/// ARIA landmark and widget roles of the same.
+const CHROME_ROLES: &[&str] = &[
+    "navigation",
+    "banner",
+    "contentinfo",
+    "complementary",
+    "search",
+    "dialog",
+    "alertdialog",
+    "menu",
+    "menubar",
+];
+
+/// Class and id words that mark sidebars, tables of contents, cookie banners,
+/// "Edit this page" links and pagination.
+const UNLIKELY: &[&str] = &[
+    "sidebar",
+    "toc",
+    "table-of-contents",
+    "breadcrumb",
+    "breadcrumbs",
+    "pagination",
+    "pager",
+    "paginator",
+    "cookie",
+    "consent",
+    "banner",
+    "edit-this-page",
+    "edit-page",
+    "navbar",
+    "menu",
+    "footer",
+    "social",
+    "share",
+    "skip-link",
+    "announcement",
+];
+
+/// Class and id words that outweigh an unlikely one, e.g. `main-sidebar-content`.
+const LIKELY: &[&str] = &["article", "content", "main", "markdown", "prose"];
+
+/// Blocks whose text is credited to the containers around them.
+const BLOCKS: &[&str] = &["p", "pre", "td", "blockquote", "li", "dd"];
+
+/// Shortest block text, in characters, that counts towards a score.
+const MIN_BLOCK_TEXT: usize = 25;
+
+/// Returns the HTML of the main content of `html`, or of the whole document
+/// without its chrome when nothing stands out.
+pub fn extract(mut html: Html) -> String {
+    let Some(root) = best_candidate(&html) else {
+        let chrome: Vec<NodeId> = html
+            .root_element()
+            .descendent_elements()
+            .filter(|element| is_chrome(*element))
+            .map(|element| element.id())
+            .collect();
+        detach(&mut html, chrome);
+        return html.html();
+    };
+
+    let dropped: Vec<NodeId> = ElementRef::wrap(html.tree.get(root).expect("node exists"))
+        .expect("candidate is an element")
+        .descendent_elements()
+        .skip(1)
+        .filter(|element| is_chrome_element(*element) || is_link_list(*element))
+        .map(|element| element.id())
+        .collect();
+    detach(&mut html, dropped);
+
+    ElementRef::wrap(html.tree.get(root).expect("node exists"))
+        .expect("candidate is an element")
+        .html()
+}
+
+/// Scores the containers of every block of text outside the chrome by its
+/// length and comma count, like Readability, then discounts each by its link
+/// density.
+fn best_candidate(html: &Html) -> Option<NodeId> {
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+
+    for block in html.root_element().descendent_elements() {
+        if !BLOCKS.contains(&block.value().name())
+            || block
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .chain([block])
+                .any(is_chrome)
+        {
+            continue;
+        }
+        let text = text(block);
+        let length = text.chars().count();
+        if length < MIN_BLOCK_TEXT {
+            continue;
+        }
+
+        let score = 1.0 + text.matches(',').count() as f64 + (length as f64 / 100.0).min(3.0);
+        let containers = block.ancestors().filter_map(ElementRef::wrap).take(3);
+        for (level, container) in containers.enumerate() {
+            let divider = if level == 0 { 1.0 } else { 2.0 * level as f64 };
+            *scores
+                .entry(container.id())
+                .or_insert_with(|| initial_score(container)) += score / divider;
+        }
+    }
+
+    scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let element = ElementRef::wrap(html.tree.get(id)?)?;
+            Some((id, score * (1.0 - link_density(element))))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(id, _)| id)
+}
+
+/// Head start for landmarks and the usual content containers.
+fn initial_score(element: ElementRef) -> f64 {
+    if is_landmark(element) {
+        return 25.0;
+    }
+
+    match element.value().name() {
+        "div" | "section" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "ol" | "ul" | "dl" | "th" => -3.0,
+        _ => 0.0,
+    }
+}
+
+fn is_landmark(element: ElementRef) -> bool {
+    matches!(element.value().name(), "main" | "article") || element.attr("role") == Some("main")
+}
+
+fn holds_landmark(element: ElementRef) -> bool {
+    element.descendent_elements().any(is_landmark)
+}
+
+/// Chrome by its tag, role or class and id words, unless it wraps the page's
+/// `<main>` or `<article>`.
+fn is_chrome(element: ElementRef) -> bool {
+    (is_chrome_element(element) || is_unlikely(element)) && !holds_landmark(element)
+}
+
+/// Chrome by its tag or ARIA role, or hidden.
+fn is_chrome_element(element: ElementRef) -> bool {
+    let value = element.value();
+
+    CHROME_TAGS.contains(&value.name())
+        || value
+            .attr("role")
+            .is_some_and(|role| CHROME_ROLES.contains(&role))
+        || value.attr("hidden").is_some()
+        || value.attr("aria-hidden") == Some("true")
+}
+
+/// Whether the class or id names a sidebar, banner, menu and the like.
+fn is_unlikely(element: ElementRef) -> bool {
+    let value = element.value();
+    if matches!(value.name(), "body" | "html") {
+        return false;
+    }
+
+    let names: Vec<String> = value
+        .classes()
+        .chain(value.id())
+        .map(str::to_ascii_lowercase)
+        .collect();
+    let mentions = |words: &[&str]| {
+        names
+            .iter()
+            .any(|name| words.iter().any(|word| has_word(name, word)))
+    };
+
+    mentions(UNLIKELY) && !mentions(LIKELY)
+}
+
+/// Whether `name` contains `word` as whole dash- or underscore-separated
+/// parts, so `theme-doc-toc-mobile` has `toc` but `stock` does not.
+fn has_word(name: &str, word: &str) -> bool {
+    let parts: Vec<&str> = name.split(['-', '_']).collect();
+    let word: Vec<&str> = word.split('-').collect();
+
+    parts
+        .windows(word.len())
+        .any(|window| window == word.as_slice())
+}
+
+/// Blocks that are almost all links, such as "Previous / Next" pagination or
+/// an inline table of contents.
+fn is_link_list(element: ElementRef) -> bool {
+    matches!(element.value().name(), "div" | "section" | "ul" | "ol")
+        && !element
+            .descendent_elements()
+            .any(|child| matches!(child.value().name(), "p" | "pre" | "table"))
+        && link_density(element) >= 0.75
+}
+
+/// Share of the element's text that sits inside links.
+fn link_density(element: ElementRef) -> f64 {
+    let length = text(element).chars().count();
+    if length == 0 {
+        return 0.0;
+    }
+
+    let links: usize = element
+        .descendent_elements()
+        .filter(|child| child.value().name() == "a")
+        .map(|link| text(link).chars().count())
+        .sum();
+
+    links as f64 / length as f64
+}
@@ -0,0 +1,154 @@
+//! Page metadata from `<head>`: title, description, canonical URL, social
+//! cards, robots directives and language alternates.
+
+use std::collections::BTreeMap;
+
+use scraper::{ElementRef, Html};
+
+use super::{base_url, selector, text, Error};
+
+/// What a page says about itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageMeta {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// `<link rel="canonical">`, made absolute.
+    pub canonical: Option<String>,
+    /// `lang` of the `<html>` element.
+    pub lang: Option<String>,
+    /// `og:*` properties without the prefix, e.g. `title` or `image`.
+    pub open_graph: BTreeMap<String, String>,
+    /// `twitter:*` names without the prefix, e.g. `card` or `site`.
+    pub twitter: BTreeMap<String, String>,
+    /// Lowercased `robots` directives, e.g. `["noindex", "nofollow"]`.
+    pub robots: Vec<String>,
+    /// Translations from `<link rel="alternate" hreflang>`.
+    pub alternates: Vec<Alternate>,
+    /// The `generator` meta tag, e.g. `Docusaurus v3.4.0`.
+    pub generator: Option<String>,
+}
+
+impl PageMeta {
+    /// Whether the page asks not to be indexed.
+    pub fn noindex(&self) -> bool {
+        self.robots
+            .iter()
+            .any(|directive| directive == "noindex" || directive == "none")
+    }
+}
+
+/// A version of the page in another language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alternate {
+    pub hreflang: String,
+    pub href: String,
+}
+
+/// Reads the metadata of `content`. Links are made absolute against `url`
+/// and `<base href>`; pass an empty `url` to keep them as written.
+pub fn page_meta(content: &str, url: &str) -> Result<PageMeta, Error> {
+    read(
+        &Html::parse_document(content),
+        Some(url).filter(|url| !url.is_empty()),
+    )
+}
+
+/// Reads the metadata of a parsed page, for callers converting it as well.
+pub(super) fn read(document: &Html, url: Option<&str>) -> Result<PageMeta, Error> {
+    let base = base_url(document, url)?;
+    let absolute = |href: &str| match &base {
+        Some(base) => base
+            .join(href.trim())
+            .map_or_else(|_| href.to_string(), String::from),
+        None => href.to_string(),
+    };
+
+    let mut meta = PageMeta {
+        lang: attr(document.root_element(), "lang"),
+        ..PageMeta::default()
+    };
+
+    for element in document.select(&selector("title, meta, link")) {
+        match element.value().name() {
+            "title" if meta.title.is_none() => {
+                meta.title = Some(text(element)).filter(|title| !title.is_empty())
+            }
+            "meta" => read_meta(&mut meta, element),
+            "link" => {
+                let rel = element.value().attr("rel").unwrap_or_default();
+                let Some(href) = attr(element, "href") else {
+                    continue;
+                };
+                let rels: Vec<String> = rel
+                    .split_whitespace()
+                    .map(str::to_ascii_lowercase)
+                    .collect();
+
+                if rels.iter().any(|rel| rel == "canonical") && meta.canonical.is_none() {
+                    meta.canonical = Some(absolute(&href));
+                }
+                if rels.iter().any(|rel| rel == "alternate") {
+                    if let Some(hreflang) = attr(element, "hreflang") {
+                        meta.alternates.push(Alternate {
+                            hreflang,
+                            href: absolute(&href),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(image) = meta.open_graph.get_mut("image") {
+        *image = absolute(image);
+    }
+    if let Some(image) = meta.twitter.get_mut("image") {
+        *image = absolute(image);
+    }
+
+    Ok(meta)
+}
+
+fn read_meta(meta: &mut PageMeta, element: ElementRef) {
+    let Some(content) = attr(element, "content") else {
+        return;
+    };
+    // OpenGraph uses `property`, but `name` is common in the wild.
+    let Some(key) = element
+        .value()
+        .attr("property")
+        .or(element.value().attr("name"))
+        .map(|key| key.trim().to_ascii_lowercase())
+    else {
+        return;
+    };
+
+    if let Some(field) = key.strip_prefix("og:") {
+        meta.open_graph.entry(field.to_string()).or_insert(content);
+    } else if let Some(field) = key.strip_prefix("twitter:") {
+        meta.twitter.entry(field.to_string()).or_insert(content);
+    } else {
+        match key.as_str() {
+            "description" if meta.description.is_none() => meta.description = Some(content),
+            "generator" if meta.generator.is_none() => meta.generator = Some(content),
+            "robots" => meta.robots.extend(
+                content
+                    .split(',')
+                    .map(|directive| directive.trim().to_ascii_lowercase())
+                    .filter(|directive| !directive.is_empty()),
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// Trimmed, non-empty attribute value.
+fn attr(element: ElementRef, name: &str) -> Option<String> {
+    element
+        .value()
+        .attr(name)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(String::from)
+}
@@ -0,0 +1,278 @@
+//! Built-in rules for the documentation frameworks most sites we index are
+//! built with: where the content lives, what to drop around it and how its
+//! tabs are marked up.
+
+use std::str::FromStr;
+
+use ego_tree::NodeId;
+use html5ever::local_name;
+use scraper::node::Text;
+use scraper::{ElementRef, Html, Node, Selector};
+
+use super::{detach, element_node, selector, text, Error};
+
+/// A documentation framework with built-in conversion rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Docusaurus,
+    Vitepress,
+    Mkdocs,
+    Sphinx,
+    Gitbook,
+    Nextra,
+    Mintlify,
+}
+
+/// How a framework marks up tabbed content.
+enum Tabs {
+    /// `role="tab"` labels in a `role="tablist"`, and `role="tabpanel"`
+    /// panels next to it.
+    Aria,
+    /// Labels and panels inside a tab set element, paired by position.
+    Classes {
+        set: &'static str,
+        label: &'static str,
+        panel: &'static str,
+    },
+}
+
+struct Rules {
+    /// Elements only this framework renders.
+    markers: &'static [&'static str],
+    /// Content roots, tried in order.
+    content: &'static [&'static str],
+    /// Chrome inside or around the content root.
+    exclude: &'static [&'static str],
+    tabs: &'static [Tabs],
+}
+
+impl Preset {
+    pub const ALL: &'static [Preset] = &[
+        Preset::Docusaurus,
+        Preset::Vitepress,
+        Preset::Mkdocs,
+        Preset::Sphinx,
+        Preset::Gitbook,
+        Preset::Nextra,
+        Preset::Mintlify,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Docusaurus => "docusaurus",
+            Preset::Vitepress => "vitepress",
+            Preset::Mkdocs => "mkdocs",
+            Preset::Sphinx => "sphinx",
+            Preset::Gitbook => "gitbook",
+            Preset::Nextra => "nextra",
+            Preset::Mintlify => "mintlify",
+        }
+    }
+
+    /// Finds the framework of `document` from its `generator` meta tag, or
+    /// failing that from elements only that framework renders.
+    pub fn detect(document: &Html) -> Option<Preset> {
+        let generator = Selector::parse("meta[name=generator]").expect("valid selector");
+        let from_generator = document
+            .select(&generator)
+            .filter_map(|meta| meta.value().attr("content"))
+            .find_map(|content| {
+                let content = content.to_ascii_lowercase();
+                Preset::ALL
+                    .iter()
+                    .copied()
+                    .find(|preset| content.contains(preset.name()))
+            });
+
+        from_generator.or_else(|| {
+            Preset::ALL.iter().copied().find(|preset| {
+                preset
+                    .rules()
+                    .markers
+                    .iter()
+                    .any(|marker| document.select(&selector(marker)).next().is_some())
+            })
+        })
+    }
+
+    /// Selectors of the framework's chrome, dropped before conversion.
+    pub fn exclude(self) -> Vec<Selector> {
+        self.rules().exclude.iter().map(|s| selector(s)).collect()
+    }
+
+    /// The first content root of `document` that exists.
+    pub fn content(self, document: &Html) -> Option<Selector> {
+        self.rules()
+            .content
+            .iter()
+            .map(|s| selector(s))
+            .find(|content| document.select(content).next().is_some())
+    }
+
+    /// Puts every tab panel under a bold paragraph with its label and drops
+    /// the tab bar, so hidden tabs read like the rest of the page.
+    pub fn flatten_tabs(self, document: &mut Html) {
+        for tabs in self.rules().tabs {
+            for (labels, panels) in tab_sets(document, tabs) {
+                let titles: Vec<String> = labels
+                    .iter()
+                    .map(|&label| {
+                        document
+                            .tree
+                            .get(label)
+                            .and_then(ElementRef::wrap)
+                            .map(text)
+                            .unwrap_or_default()
+                    })
+                    .collect();
+
+                for (panel, title) in panels.into_iter().zip(titles) {
+                    if let Some(mut panel) = document.tree.get_mut(panel) {
+                        panel
+                            .insert_before(element_node(local_name!("p")))
+                            .append(element_node(local_name!("strong")))
+                            .append(Node::Text(Text { text: title.into() }));
+                    }
+                }
+                detach(document, labels);
+            }
+        }
+    }
+
+    fn rules(self) -> Rules {
+        match self {
+            Preset::Docusaurus => Rules {
+                markers: &["#__docusaurus"],
+                content: &[".theme-doc-markdown", "article", "main"],
+                exclude: &[
+                    ".theme-doc-toc-mobile",
+                    ".theme-doc-breadcrumbs",
+                    ".theme-doc-version-badge",
+                    ".theme-doc-footer",
+                    ".pagination-nav",
+                ],
+                tabs: &[Tabs::Aria],
+            },
+            Preset::Vitepress => Rules {
+                markers: &["#VPContent", ".VPDoc"],
+                content: &[".vp-doc", "main"],
+                exclude: &[
+                    ".VPLocalNav",
+                    ".VPDocFooter",
+                    ".VPDocAsideOutline",
+                    ".edit-link",
+                ],
+                tabs: &[Tabs::Classes {
+                    set: ".vp-code-group",
+                    label: ".tabs > label",
+                    panel: ".blocks > div",
+                }],
+            },
+            Preset::Mkdocs => Rules {
+                markers: &[".md-container", "[data-md-component=content]"],
+                content: &["article.md-content__inner", ".md-content"],
+                exclude: &[
+                    "a.headerlink",
+                    ".md-content__button",
+                    ".md-source-file",
+                    ".md-feedback",
+                ],
+                tabs: &[Tabs::Classes {
+                    set: ".tabbed-set",
+                    label: ".tabbed-labels > label",
+                    panel: ".tabbed-content > .tabbed-block",
+                }],
+            },
+            Preset::Sphinx => Rules {
+                markers: &[".sphinxsidebar", ".wy-nav-content", "#furo-main-content"],
+                content: &["[role=main]", "div.body", ".wy-nav-content"],
+                exclude: &[
+                    "a.headerlink",
+                    ".related",
+                    ".sphinxsidebar",
+                    ".wy-breadcrumbs",
+                    ".rst-footer-buttons",
+                    ".prev-next-area",
+                ],
+                tabs: &[
+                    Tabs::Aria,
+                    Tabs::Classes {
+                        set: ".sd-tab-set",
+                        label: ".sd-tab-label",
+                        panel: ".sd-tab-content",
+                    },
+                ],
+            },
+            Preset::Gitbook => Rules {
+                markers: &[".book-summary", ".gitbook-root"],
+                content: &[".markdown-section", "main"],
+                exclude: &["aside", ".book-summary", ".navigation"],
+                tabs: &[Tabs::Aria],
+            },
+            Preset::Nextra => Rules {
+                markers: &[
+                    ".nextra-nav-container",
+                    ".nextra-toc",
+                    ".nextra-sidebar-container",
+                ],
+                content: &["article main", "main"],
+                exclude: &[
+                    ".nextra-toc",
+                    ".nextra-sidebar-container",
+                    ".nextra-breadcrumb",
+                    "a.subheading-anchor",
+                ],
+                tabs: &[Tabs::Aria],
+            },
+            Preset::Mintlify => Rules {
+                markers: &["#content-area"],
+                content: &["#content-area"],
+                exclude: &[
+                    "#table-of-contents",
+                    "#pagination",
+                    "#sidebar",
+                    "#navbar",
+                    "#footer",
+                    ".eyebrow",
+                ],
+                tabs: &[Tabs::Aria],
+            },
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Preset::ALL
+            .iter()
+            .copied()
+            .find(|preset| preset.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| Error::Preset(s.to_string()))
+    }
+}
+
+/// Label and panel elements of every tab set in `document`.
+fn tab_sets(document: &Html, tabs: &Tabs) -> Vec<(Vec<NodeId>, Vec<NodeId>)> {
+    let ids = |parent: ElementRef, s: &str| -> Vec<NodeId> {
+        parent
+            .select(&selector(s))
+            .map(|element| element.id())
+            .collect()
+    };
+
+    match tabs {
+        Tabs::Aria => document
+            .select(&selector("[role=tablist]"))
+            .filter_map(|list| {
+                let set = list.parent().and_then(ElementRef::wrap)?;
+                Some((ids(list, "[role=tab]"), ids(set, "[role=tabpanel]")))
+            })
+            .collect(),
+        Tabs::Classes { set, label, panel } => document
+            .select(&selector(set))
+            .map(|set| (ids(set, label), ids(set, panel)))
+            .collect(),
+    }
+}
@@ -1,12 +1,32 @@
+//! Keyword extraction (YAKE) used to build search synonyms and suggestions.
+
 use std::collections::HashSet;
+use std::sync::OnceLock;
 
 use keyword_extraction::yake::{Yake, YakeParams};
 use stop_words::{get, LANGUAGE};
 use tokenizers::{normalizers::BertNormalizer, NormalizedString, Normalizer};
 
-pub fn extract<'a>(text: &'a str, n: usize) -> anyhow::Result<Vec<String>> {
-    let stop_words = get(LANGUAGE::English);
-    let yake = Yake::new(YakeParams::WithDefaults(text, &stop_words));
+/// Errors returned while extracting keywords.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to normalize keyword: {0}")]
+    Normalize(String),
+}
+
+impl Error {
+    /// Short machine-readable name of the error, e.g. `"normalize"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Normalize(_) => "normalize",
+        }
+    }
+}
+
+/// Extracts up to `n` ranked keywords from `text`, split into single
+/// normalized latin words.
+pub fn extract(text: &str, n: usize) -> Result<Vec<String>, Error> {
+    let yake = Yake::new(YakeParams::WithDefaults(text, stop_words()));
 
     let ret: Vec<String> = yake
         .get_ranked_keywords(n)
@@ -18,6 +38,8 @@ pub fn extract<'a>(text: &'a str, n: usize) -> anyhow::Result<Vec<String>> {
         })
         .map(|word| remove_emoji(&word))
         .map(|word| bert_normalize(&word))
+        .collect::<Result<Vec<String>, Error>>()?
+        .into_iter()
         .filter(|word| word.len() >= 3 && word.len() <= 18)
         .filter(|word| count_numbers(word) < count_letters(word))
         .filter(|word| is_latin(word))
@@ -28,6 +50,12 @@ pub fn extract<'a>(text: &'a str, n: usize) -> anyhow::Result<Vec<String>> {
     Ok(ret)
 }
 
+/// The English stop-word list, loaded once per process.
+pub fn stop_words() -> &'static [String] {
+    static STOP_WORDS: OnceLock<Vec<String>> = OnceLock::new();
+    STOP_WORDS.get_or_init(|| get(LANGUAGE::English))
+}
+
 fn is_latin(word: &str) -> bool {
     word.chars()
         .all(|c| c.is_ascii_alphabetic() || c.is_ascii_digit() || c.is_ascii_punctuation())
@@ -41,12 +69,16 @@ fn remove_emoji(string: &str) -> String {
     graphemes.filter(not_emoji).collect()
 }
 
-fn bert_normalize(text: &str) -> String {
+fn bert_normalize(text: &str) -> Result<String, Error> {
+    static NORMALIZER: OnceLock<BertNormalizer> = OnceLock::new();
+
     let mut text = NormalizedString::from(text);
-    let bert_normalizer = BertNormalizer::new(true, false, Some(true), true);
-    let _ = bert_normalizer.normalize(&mut text);
+    NORMALIZER
+        .get_or_init(|| BertNormalizer::new(true, false, Some(true), true))
+        .normalize(&mut text)
+        .map_err(|e| Error::Normalize(e.to_string()))?;
 
-    text.get().to_string()
+    Ok(text.get().to_string())
 }
 
 fn count_numbers(word: &str) -> usize {
@@ -1,60 +1,25 @@
-use ::glob_match as glob;
-
-mod chunk;
-mod git;
-mod html;
-mod keywords;
+//! Ingestion pipeline shared by Canary: HTML→markdown conversion, chunking,
+//! keyword extraction and shallow git clones.
+//!
+//! The Elixir bindings (`Canary.Native`) live behind the default `nif`
+//! feature. Rust consumers can depend on the crate with
+//! `default-features = false` to get the exact same behavior without rustler.
+
+pub mod chunk;
+pub mod code;
+pub mod front_matter;
+pub mod git;
+pub mod html;
+pub mod keywords;
+pub mod markdown;
+pub mod pipeline;
+pub mod tokenizer;
+
+/// Glob matching used for crawler include/exclude patterns.
+pub use glob_match::glob_match;
+
+#[cfg(feature = "nif")]
+mod nif;
 
 #[cfg(test)]
 mod tests;
-
-rustler::init!(
-    "Elixir.Canary.Native",
-    [
-        chunk_text,
-        chunk_markdown,
-        html_to_md,
-        clone_depth,
-        extract_keywords,
-        stopwords,
-        glob_match,
-    ]
-);
-
-#[rustler::nif]
-fn chunk_text<'a>(content: &'a str, max_tokens: usize) -> Vec<String> {
-    chunk::chunk_text(content, max_tokens).unwrap()
-}
-
-#[rustler::nif]
-fn chunk_markdown<'a>(content: &'a str, max_tokens: usize) -> Vec<String> {
-    chunk::chunk_markdown(content, max_tokens).unwrap()
-}
-
-#[rustler::nif(schedule = "DirtyCpu")]
-fn html_to_md<'a>(content: &'a str) -> String {
-    html::to_md(content).unwrap()
-}
-
-#[rustler::nif(schedule = "DirtyIo")]
-fn clone_depth<'a>(repo_url: &'a str, dest_path: &'a str, depth: i32) -> bool {
-    match git::clone_depth(repo_url, dest_path, depth) {
-        Ok(_) => true,
-        Err(_) => false,
-    }
-}
-
-#[rustler::nif]
-fn extract_keywords<'a>(content: &'a str, n: usize) -> Vec<String> {
-    keywords::extract(content, n).unwrap()
-}
-
-#[rustler::nif]
-fn stopwords() -> Vec<String> {
-    stop_words::get(stop_words::LANGUAGE::English)
-}
-
-#[rustler::nif]
-fn glob_match<'a>(pattern: &'a str, path: &'a str) -> bool {
-    glob::glob_match(pattern, path)
-}
@@ -0,0 +1,270 @@
+//! Structural scan of markdown documents, shared by the chunkers.
+
+use std::ops::Range;
+
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+
+/// A heading and the byte offset where it starts in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    pub level: usize,
+    /// Anchor from a `{#id}` attribute, e.g. `## Usage {#usage}`.
+    pub id: Option<String>,
+    pub title: String,
+    pub start: usize,
+}
+
+/// A fenced code block and its byte range in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fence {
+    /// Opening line with its info string, e.g. ` ```python `.
+    pub opener: String,
+    /// What its lines start with inside a blockquote or list, e.g. `> `;
+    /// list markers become spaces.
+    pub prefix: String,
+    /// Fence marker that closes the block, e.g. ` ``` `.
+    pub closer: String,
+    pub start: usize,
+    /// Byte offset where the closing line starts, or `end` for an unclosed
+    /// fence.
+    pub body_end: usize,
+    /// Byte offset just past the closing line, or past the last line of the
+    /// document for an unclosed fence.
+    pub end: usize,
+}
+
+/// A GFM table and the byte ranges of its body rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    /// Header row and the delimiter line under it.
+    pub header: String,
+    pub start: usize,
+    pub end: usize,
+    /// One range per body row, without the line break.
+    pub rows: Vec<Range<usize>>,
+}
+
+impl Table {
+    /// Byte offset just past the header rows.
+    pub fn header_end(&self) -> usize {
+        self.start + self.header.len()
+    }
+
+    /// The row `offset` falls strictly inside of, if any. The header row and
+    /// delimiter line count as a single row.
+    pub fn row_at(&self, offset: usize) -> Option<Range<usize>> {
+        std::iter::once(self.start..self.header_end())
+            .chain(self.rows.iter().cloned())
+            .find(|row| row.start < offset && offset < row.end)
+    }
+
+    /// Whether `offset` is at or past the first body row, so a chunk starting
+    /// there needs the header repeated.
+    pub fn continues_at(&self, offset: usize) -> bool {
+        self.rows
+            .first()
+            .is_some_and(|row| row.start <= offset && offset < self.end)
+    }
+}
+
+/// Collects every ATX and setext heading in `content`, in document order.
+pub fn headings(content: &str) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut current: Option<Heading> = None;
+
+    for (event, range) in Parser::new_ext(content, Options::all()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, id, .. }) => {
+                current = Some(Heading {
+                    level: level as usize,
+                    id: id.map(|id| id.to_string()),
+                    title: String::new(),
+                    start: range.start,
+                });
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = current.as_mut() {
+                    heading.title.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(mut heading) = current.take() {
+                    heading.title = heading.title.trim().to_string();
+                    headings.push(heading);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    headings
+}
+
+/// Collects every fenced code block in `content`, in document order.
+pub fn fences(content: &str) -> Vec<Fence> {
+    Parser::new_ext(content, Options::all())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(_))) => {
+                let block = content[range.clone()].trim_end();
+                let opener = block.lines().next().unwrap_or_default().trim();
+                let marker = opener.chars().next()?;
+                let closer: String = opener.chars().take_while(|&c| c == marker).collect();
+                let prefix = content[line_start(content, range.start)..range.start]
+                    .chars()
+                    .map(|c| {
+                        if c == '>' || c.is_whitespace() {
+                            c
+                        } else {
+                            ' '
+                        }
+                    })
+                    .collect();
+                let last = block.rfind('\n').map_or(block.len(), |newline| newline + 1);
+                let closing = strip_container(&block[last..]).trim();
+                let body_end =
+                    if closing.starts_with(&closer) && closing.chars().all(|c| c == marker) {
+                        last
+                    } else {
+                        block.len()
+                    };
+
+                Some(Fence {
+                    opener: opener.to_string(),
+                    prefix,
+                    closer,
+                    start: range.start,
+                    body_end: range.start + body_end,
+                    end: range.start + block.len(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Byte offset where the line holding `offset` starts.
+pub fn line_start(content: &str, offset: usize) -> usize {
+    content[..offset]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1)
+}
+
+/// `line` without its blockquote markers and indentation.
+pub fn strip_container(line: &str) -> &str {
+    line.trim_start_matches(|c: char| c == '>' || c.is_whitespace())
+}
+
+/// Collects every table in `content`, in document order.
+pub fn tables(content: &str) -> Vec<Table> {
+    let mut tables = Vec::new();
+    let mut current: Option<Table> = None;
+
+    for (event, range) in Parser::new_ext(content, Options::all()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Table(_)) => {
+                current = Some(Table {
+                    header: String::new(),
+                    start: range.start,
+                    end: range.start + content[range].trim_end().len(),
+                    rows: Vec::new(),
+                });
+            }
+            Event::Start(Tag::TableRow) => {
+                if let Some(table) = current.as_mut() {
+                    let row = content[range.clone()].trim_end();
+                    table.rows.push(range.start..range.start + row.len());
+                }
+            }
+            Event::End(TagEnd::Table) => {
+                if let Some(mut table) = current.take() {
+                    let body = table.rows.first().map_or(table.end, |row| row.start);
+                    table.header = content[table.start..body].trim_end().to_string();
+                    tables.push(table);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    tables
+}
+
+/// Whether `content` consists of nothing but headings, e.g. a lone `## Usage`.
+pub fn is_heading_only(content: &str) -> bool {
+    let mut in_heading = false;
+    let mut seen = false;
+
+    for event in Parser::new_ext(content, Options::all()) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => {
+                in_heading = true;
+                seen = true;
+            }
+            Event::End(TagEnd::Heading(_)) => in_heading = false,
+            _ if in_heading => {}
+            _ => return false,
+        }
+    }
+
+    seen
+}
+
+/// Whether `content` is just the opening line of a fenced code block,
+/// e.g. a lone ` ```python `.
+pub fn is_fence_opener(content: &str) -> bool {
+    let line = strip_container(content.trim());
+
+    !line.contains('\n') && (line.starts_with("```") || line.starts_with("~~~"))
+}
+
+/// Whether `content` is only blockquote markers, optionally with a GitHub
+/// alert such as `> [!TIP]`, which says nothing without the quote's body.
+pub fn is_quote_marker(content: &str) -> bool {
+    let content = content.trim();
+
+    content.starts_with('>')
+        && content.lines().all(|line| {
+            let rest = strip_container(line);
+            rest.is_empty() || (rest.starts_with("[!") && rest.ends_with(']'))
+        })
+}
+
+/// Splits `content` at every heading of `max_level` or above (so `2` splits
+/// at H1 and H2), trimming surrounding whitespace and dropping empty parts.
+pub fn sections(content: &str, headings: &[Heading], max_level: usize) -> Vec<Range<usize>> {
+    let mut bounds: Vec<usize> = headings
+        .iter()
+        .filter(|heading| heading.level <= max_level)
+        .map(|heading| heading.start)
+        .collect();
+    bounds.insert(0, 0);
+    bounds.push(content.len());
+
+    bounds
+        .windows(2)
+        .filter_map(|w| {
+            let text = &content[w[0]..w[1]];
+            let start = w[0] + (text.len() - text.trim_start().len());
+            let end = w[0] + text.trim_end().len();
+            (start < end).then_some(start..end)
+        })
+        .collect()
+}
+
+/// Titles of the headings in effect at `offset`, outermost first.
+///
+/// A heading that starts exactly at `offset` is included, so a chunk that
+/// opens with `## Usage` reports `Usage` as its innermost section.
+pub fn breadcrumb(headings: &[Heading], offset: usize) -> Vec<String> {
+    let mut stack: Vec<&Heading> = Vec::new();
+
+    for heading in headings.iter().take_while(|h| h.start <= offset) {
+        while stack.last().is_some_and(|h| h.level >= heading.level) {
+            stack.pop();
+        }
+        stack.push(heading);
+    }
+
+    stack.into_iter().map(|h| h.title.clone()).collect()
+}
@@ -0,0 +1,547 @@
+mod error;
+
+use std::collections::HashMap;
+
+use crate::{chunk, code, front_matter, git, html, keywords, pipeline, tokenizer};
+use error::NifError;
+use rustler::{Atom, Decoder, Env, NifMap, NifUnitEnum, ResourceArc, Term, TermType};
+
+rustler::init!(
+    "Elixir.Canary.Native",
+    [
+        chunk_text,
+        chunk_markdown,
+        new_splitter,
+        split,
+        chunk_hierarchical,
+        chunk_code,
+        parse_markdown_document,
+        html_to_md,
+        html_to_sections,
+        html_to_page,
+        extract_page_meta,
+        extract_links,
+        clone_depth,
+        extract_keywords,
+        process_pages,
+        stopwords,
+        glob_match,
+        count_tokens,
+        truncate,
+    ],
+    load = load
+);
+
+#[allow(non_local_definitions)]
+fn load(env: Env, _: Term) -> bool {
+    rustler::resource!(SplitterResource, env);
+    true
+}
+
+struct SplitterResource(chunk::Splitter);
+
+#[derive(NifUnitEnum)]
+enum SplitterKind {
+    Text,
+    Markdown,
+}
+
+#[derive(NifMap)]
+struct Chunk {
+    text: String,
+    content: String,
+    start: usize,
+    end: usize,
+    tokens: usize,
+    headings: Vec<String>,
+}
+
+impl From<chunk::Chunk> for Chunk {
+    fn from(chunk: chunk::Chunk) -> Self {
+        Self {
+            text: chunk.text,
+            content: chunk.content,
+            start: chunk.start,
+            end: chunk.end,
+            tokens: chunk.tokens,
+            headings: chunk.headings,
+        }
+    }
+}
+
+fn into_chunks(chunks: Vec<chunk::Chunk>) -> Vec<Chunk> {
+    chunks.into_iter().map(Chunk::from).collect()
+}
+
+/// Missing keys fall back to `defaults`.
+fn chunk_options(opts: Term, defaults: chunk::Options) -> Result<chunk::Options, NifError> {
+    Ok(chunk::Options {
+        tokenizer: get_opt(opts, "tokenizer")?.unwrap_or(defaults.tokenizer),
+        min_tokens: get_opt(opts, "min_tokens")?.or(defaults.min_tokens),
+        max_tokens: get_opt(opts, "max_tokens")?.unwrap_or(defaults.max_tokens),
+        overlap_tokens: get_opt(opts, "overlap_tokens")?.unwrap_or(defaults.overlap_tokens),
+        merge_below_tokens: get_opt(opts, "merge_below_tokens")?.or(defaults.merge_below_tokens),
+        header: header_options(opts)?.or(defaults.header),
+    })
+}
+
+/// `header: true` turns the contextual header on; `title` and
+/// `header_separator` configure it.
+fn header_options(opts: Term) -> Result<Option<chunk::Header>, NifError> {
+    if !get_opt(opts, "header")?.unwrap_or(false) {
+        return Ok(None);
+    }
+
+    let defaults = chunk::Header::default();
+    Ok(Some(chunk::Header {
+        title: get_opt(opts, "title")?,
+        separator: get_opt(opts, "header_separator")?.unwrap_or(defaults.separator),
+    }))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn chunk_text(content: &str, opts: Term) -> Result<Vec<Chunk>, NifError> {
+    let opts = chunk_options(opts, chunk::Options::default())?;
+    Ok(into_chunks(chunk::chunk_text(content, &opts)?))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn chunk_markdown(content: &str, opts: Term) -> Result<Vec<Chunk>, NifError> {
+    let opts = chunk_options(opts, chunk::Options::default())?;
+    Ok(into_chunks(chunk::chunk_markdown(content, &opts)?))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn new_splitter(kind: SplitterKind, opts: Term) -> Result<ResourceArc<SplitterResource>, NifError> {
+    let opts = chunk_options(opts, chunk::Options::default())?;
+    let splitter = match kind {
+        SplitterKind::Text => chunk::Splitter::text(&opts)?,
+        SplitterKind::Markdown => chunk::Splitter::markdown(&opts)?,
+    };
+
+    Ok(ResourceArc::new(SplitterResource(splitter)))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn split(splitter: ResourceArc<SplitterResource>, content: &str) -> Result<Vec<Chunk>, NifError> {
+    Ok(into_chunks(splitter.0.chunks(content)?))
+}
+
+#[derive(NifMap)]
+struct LinkedChunk {
+    id: String,
+    parent_id: Option<String>,
+    text: String,
+    content: String,
+    start: usize,
+    end: usize,
+    tokens: usize,
+    headings: Vec<String>,
+}
+
+impl From<chunk::LinkedChunk> for LinkedChunk {
+    fn from(linked: chunk::LinkedChunk) -> Self {
+        let chunk = linked.chunk;
+
+        Self {
+            id: linked.id,
+            parent_id: linked.parent_id,
+            text: chunk.text,
+            content: chunk.content,
+            start: chunk.start,
+            end: chunk.end,
+            tokens: chunk.tokens,
+            headings: chunk.headings,
+        }
+    }
+}
+
+/// `section` and `passage` take the same options as `chunk_markdown`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn chunk_hierarchical(content: &str, opts: Term) -> Result<Vec<LinkedChunk>, NifError> {
+    let defaults = chunk::HierarchyOptions::default();
+    let opts = chunk::HierarchyOptions {
+        section_level: get_opt(opts, "section_level")?.unwrap_or(defaults.section_level),
+        section: match get_opt(opts, "section")? {
+            Some(section) => chunk_options(section, defaults.section)?,
+            None => defaults.section,
+        },
+        passage: match get_opt(opts, "passage")? {
+            Some(passage) => chunk_options(passage, defaults.passage)?,
+            None => defaults.passage,
+        },
+    };
+
+    Ok(chunk::chunk_hierarchical(content, &opts)?
+        .into_iter()
+        .map(LinkedChunk::from)
+        .collect())
+}
+
+#[derive(NifMap)]
+struct CodeChunk {
+    text: String,
+    content: String,
+    start: usize,
+    end: usize,
+    tokens: usize,
+    symbols: Vec<String>,
+}
+
+impl From<chunk::Chunk> for CodeChunk {
+    fn from(chunk: chunk::Chunk) -> Self {
+        Self {
+            text: chunk.text,
+            content: chunk.content,
+            start: chunk.start,
+            end: chunk.end,
+            tokens: chunk.tokens,
+            symbols: chunk.headings,
+        }
+    }
+}
+
+/// `language` is a name or file extension, e.g. `"elixir"` or `"ex"`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn chunk_code(content: &str, language: &str, opts: Term) -> Result<Vec<CodeChunk>, NifError> {
+    let opts = chunk_options(opts, chunk::Options::default())?;
+    let language = language.parse::<code::Language>()?;
+
+    Ok(chunk::chunk_code(content, language, &opts)?
+        .into_iter()
+        .map(CodeChunk::from)
+        .collect())
+}
+
+#[derive(NifMap)]
+struct FrontMatter {
+    title: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+    sidebar_position: Option<f64>,
+    slug: Option<String>,
+    draft: bool,
+}
+
+#[derive(NifMap)]
+struct MarkdownDocument {
+    front_matter: FrontMatter,
+    body: String,
+    body_start: usize,
+}
+
+impl From<front_matter::Document> for MarkdownDocument {
+    fn from(document: front_matter::Document) -> Self {
+        let front_matter = document.front_matter;
+
+        Self {
+            front_matter: FrontMatter {
+                title: front_matter.title,
+                description: front_matter.description,
+                tags: front_matter.tags,
+                sidebar_position: front_matter.sidebar_position,
+                slug: front_matter.slug,
+                draft: front_matter.draft,
+            },
+            body: document.body,
+            body_start: document.body_start,
+        }
+    }
+}
+
+#[rustler::nif]
+fn parse_markdown_document(content: &str) -> Result<MarkdownDocument, NifError> {
+    Ok(front_matter::parse(content).into())
+}
+
+fn html_options(opts: Term) -> Result<html::Options, NifError> {
+    let defaults = html::Options::default();
+
+    Ok(html::Options {
+        main_content: get_opt(opts, "main_content")?.unwrap_or(defaults.main_content),
+        include: get_opt(opts, "include")?.unwrap_or(defaults.include),
+        exclude: get_opt(opts, "exclude")?.unwrap_or(defaults.exclude),
+        url: get_opt(opts, "url")?,
+        drop_external_links: get_opt(opts, "drop_external_links")?
+            .unwrap_or(defaults.drop_external_links),
+        preset: get_opt::<Preset>(opts, "preset")?.map(html::Preset::from),
+    })
+}
+
+#[derive(NifUnitEnum)]
+enum Preset {
+    Docusaurus,
+    Vitepress,
+    Mkdocs,
+    Sphinx,
+    Gitbook,
+    Nextra,
+    Mintlify,
+}
+
+impl From<Preset> for html::Preset {
+    fn from(preset: Preset) -> Self {
+        match preset {
+            Preset::Docusaurus => Self::Docusaurus,
+            Preset::Vitepress => Self::Vitepress,
+            Preset::Mkdocs => Self::Mkdocs,
+            Preset::Sphinx => Self::Sphinx,
+            Preset::Gitbook => Self::Gitbook,
+            Preset::Nextra => Self::Nextra,
+            Preset::Mintlify => Self::Mintlify,
+        }
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn html_to_md(content: &str, opts: Term) -> Result<String, NifError> {
+    let opts = html_options(opts)?;
+    Ok(html::to_md(content, &opts)?)
+}
+
+#[derive(NifMap)]
+struct Section {
+    id: Option<String>,
+    level: usize,
+    title: String,
+    content: String,
+}
+
+impl From<html::Section> for Section {
+    fn from(section: html::Section) -> Self {
+        Self {
+            id: section.id,
+            level: section.level,
+            title: section.title,
+            content: section.content,
+        }
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn html_to_sections(content: &str, opts: Term) -> Result<Vec<Section>, NifError> {
+    let opts = html_options(opts)?;
+    Ok(html::to_sections(content, &opts)?
+        .into_iter()
+        .map(Section::from)
+        .collect())
+}
+
+#[derive(NifMap)]
+struct PageMeta {
+    title: Option<String>,
+    description: Option<String>,
+    canonical: Option<String>,
+    lang: Option<String>,
+    open_graph: HashMap<String, String>,
+    twitter: HashMap<String, String>,
+    robots: Vec<String>,
+    noindex: bool,
+    alternates: Vec<Alternate>,
+    generator: Option<String>,
+}
+
+#[derive(NifMap)]
+struct Alternate {
+    hreflang: String,
+    href: String,
+}
+
+impl From<html::PageMeta> for PageMeta {
+    fn from(meta: html::PageMeta) -> Self {
+        Self {
+            noindex: meta.noindex(),
+            title: meta.title,
+            description: meta.description,
+            canonical: meta.canonical,
+            lang: meta.lang,
+            open_graph: meta.open_graph.into_iter().collect(),
+            twitter: meta.twitter.into_iter().collect(),
+            robots: meta.robots,
+            alternates: meta
+                .alternates
+                .into_iter()
+                .map(|alternate| Alternate {
+                    hreflang: alternate.hreflang,
+                    href: alternate.href,
+                })
+                .collect(),
+            generator: meta.generator,
+        }
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn extract_page_meta(content: &str, url: &str) -> Result<PageMeta, NifError> {
+    Ok(html::page_meta(content, url)?.into())
+}
+
+#[derive(NifMap)]
+struct Page {
+    meta: PageMeta,
+    sections: Vec<Section>,
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn html_to_page(content: &str, opts: Term) -> Result<Page, NifError> {
+    let opts = html_options(opts)?;
+    let page = html::to_page(content, &opts)?;
+    Ok(Page {
+        meta: page.meta.into(),
+        sections: page.sections.into_iter().map(Section::from).collect(),
+    })
+}
+
+#[derive(NifMap)]
+struct Link {
+    url: String,
+    text: String,
+    rel: Vec<String>,
+    same_origin: bool,
+}
+
+impl From<html::Link> for Link {
+    fn from(link: html::Link) -> Self {
+        Self {
+            url: link.url,
+            text: link.text,
+            rel: link.rel,
+            same_origin: link.same_origin,
+        }
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn extract_links(content: &str, url: &str, opts: Term) -> Result<Vec<Link>, NifError> {
+    let filter = html::LinkFilter {
+        include: get_opt(opts, "include")?.unwrap_or_default(),
+        exclude: get_opt(opts, "exclude")?.unwrap_or_default(),
+    };
+
+    Ok(html::links(content, url, &filter)?
+        .into_iter()
+        .map(Link::from)
+        .collect())
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn clone_depth(repo_url: &str, dest_path: &str, depth: i32) -> Result<String, NifError> {
+    git::clone_depth(repo_url, dest_path, depth)?;
+    Ok(dest_path.to_string())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn extract_keywords(content: &str, n: usize) -> Result<Vec<String>, NifError> {
+    Ok(keywords::extract(content, n)?)
+}
+
+#[derive(NifMap)]
+struct ProcessedPage {
+    url: String,
+    markdown: String,
+    chunks: Vec<Chunk>,
+    keywords: Vec<String>,
+}
+
+// Holds one dirty CPU scheduler while the pages run on the pipeline's own
+// pool, which is capped at half the cores.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn process_pages(pages: Vec<(String, String)>, opts: Term) -> Vec<Result<ProcessedPage, NifError>> {
+    let opts = match pipeline_options(opts) {
+        Ok(opts) => opts,
+        // Every page fails the same way, keeping one result per page.
+        Err(error) => {
+            return pages.iter().map(|_| Err(error.clone())).collect();
+        }
+    };
+
+    pipeline::process_pages(&pages, &opts)
+        .into_iter()
+        .map(|result| {
+            result
+                .map(|page| ProcessedPage {
+                    url: page.url,
+                    markdown: page.markdown,
+                    chunks: into_chunks(page.chunks),
+                    keywords: page.keywords,
+                })
+                .map_err(NifError::from)
+        })
+        .collect()
+}
+
+fn pipeline_options(opts: Term) -> Result<pipeline::Options, NifError> {
+    let defaults = pipeline::Options::default();
+
+    Ok(pipeline::Options {
+        html: html_options(opts)?,
+        chunk: chunk_options(opts, defaults.chunk)?,
+        max_keywords: get_opt(opts, "max_keywords")?.unwrap_or(defaults.max_keywords),
+    })
+}
+
+#[rustler::nif]
+fn stopwords() -> Vec<String> {
+    keywords::stop_words().to_vec()
+}
+
+#[rustler::nif]
+fn glob_match(pattern: &str, path: &str) -> bool {
+    crate::glob_match(pattern, path)
+}
+
+#[derive(NifUnitEnum)]
+enum Side {
+    Head,
+    Tail,
+    Middle,
+}
+
+impl From<Side> for tokenizer::Side {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::Head => tokenizer::Side::Head,
+            Side::Tail => tokenizer::Side::Tail,
+            Side::Middle => tokenizer::Side::Middle,
+        }
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn count_tokens(text: &str, tokenizer: &str) -> Result<usize, NifError> {
+    Ok(tokenizer::Tokenizer::load(tokenizer)?.count(text)?)
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn truncate(
+    text: &str,
+    tokenizer: &str,
+    max_tokens: usize,
+    side: Side,
+) -> Result<String, NifError> {
+    Ok(tokenizer::Tokenizer::load(tokenizer)?.truncate(text, max_tokens, side.into())?)
+}
+
+/// Reads an optional key from an options map or keyword list; missing and
+/// `nil` values fall back to defaults. Anything else, or a value of the wrong
+/// type, is `{:invalid_options, message}`.
+fn get_opt<'a, T: Decoder<'a>>(opts: Term<'a>, key: &str) -> Result<Option<T>, NifError> {
+    let atom = Atom::from_str(opts.get_env(), key)
+        .map_err(|_| NifError::invalid_options(format!("invalid key {key}")))?;
+    let value = match opts.get_type() {
+        TermType::Map => opts.map_get(atom).ok(),
+        TermType::List => opts
+            .decode::<Vec<(Atom, Term)>>()
+            .map_err(|_| NifError::invalid_options("expected a keyword list"))?
+            .into_iter()
+            .find(|(name, _)| *name == atom)
+            .map(|(_, value)| value),
+        _ => return Err(NifError::invalid_options("expected a map or keyword list")),
+    };
+
+    value
+        .map(|value| value.decode::<Option<T>>())
+        .transpose()
+        .map(Option::flatten)
+        .map_err(|_| NifError::invalid_options(format!("invalid value for {key}")))
+}
@@ -0,0 +1,56 @@
+use rustler::{Atom, Encoder, Env, Term};
+
+use crate::{chunk, code, git, html, keywords, pipeline, tokenizer};
+
+/// Encoded as `{kind, message}`, so NIFs returning `Result<T, NifError>`
+/// produce `{:error, {kind, message}}` on the Elixir side.
+#[derive(Debug, Clone)]
+pub struct NifError {
+    kind: &'static str,
+    message: String,
+}
+
+impl NifError {
+    /// Options that are not a map or keyword list, or hold a value of the
+    /// wrong type.
+    pub fn invalid_options(message: impl Into<String>) -> Self {
+        Self {
+            kind: "invalid_options",
+            message: message.into(),
+        }
+    }
+}
+
+impl Encoder for NifError {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match Atom::from_str(env, self.kind) {
+            Ok(kind) => (kind, &self.message).encode(env),
+            Err(_) => self.message.encode(env),
+        }
+    }
+}
+
+macro_rules! impl_from_module_error {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for NifError {
+                fn from(error: $error) -> Self {
+                    Self {
+                        kind: error.kind(),
+                        message: error.to_string(),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_module_error!(
+    chunk::Error,
+    code::Error,
+    git::Error,
+    html::Error,
+    keywords::Error,
+    pipeline::Error,
+    tokenizer::Error
+);
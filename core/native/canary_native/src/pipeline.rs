@@ -0,0 +1,102 @@
+//! The full per-page ingestion pipeline: html→markdown→chunks→keywords.
+
+use std::sync::OnceLock;
+
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+use crate::{chunk, html, keywords};
+
+/// Errors from any stage of the pipeline.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Html(#[from] html::Error),
+    #[error(transparent)]
+    Chunk(#[from] chunk::Error),
+    #[error(transparent)]
+    Keywords(#[from] keywords::Error),
+}
+
+impl Error {
+    /// The `kind` of the underlying stage error.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Html(e) => e.kind(),
+            Error::Chunk(e) => e.kind(),
+            Error::Keywords(e) => e.kind(),
+        }
+    }
+}
+
+/// Pipeline settings shared by every page in a batch.
+pub struct Options {
+    pub html: html::Options,
+    pub chunk: chunk::Options,
+    pub max_keywords: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            html: html::Options::default(),
+            chunk: chunk::Options::default(),
+            max_keywords: 30,
+        }
+    }
+}
+
+/// A processed page.
+pub struct Page {
+    pub url: String,
+    pub markdown: String,
+    pub chunks: Vec<chunk::Chunk>,
+    pub keywords: Vec<String>,
+}
+
+/// Runs html→markdown→chunks→keywords for every page on the pipeline's
+/// thread pool. Results are returned in input order, one per page.
+pub fn process_pages(pages: &[(String, String)], opts: &Options) -> Vec<Result<Page, Error>> {
+    pool().install(|| {
+        pages
+            .par_iter()
+            .map(|(url, html)| process_page(url, html, opts))
+            .collect()
+    })
+}
+
+/// The batch NIF runs on a dirty CPU scheduler but fans pages out to its
+/// own threads, so the pool gets half the cores and leaves the rest to the
+/// BEAM's schedulers. Concurrent batches share it.
+fn pool() -> &'static ThreadPool {
+    static POOL: OnceLock<ThreadPool> = OnceLock::new();
+
+    POOL.get_or_init(|| {
+        let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+        rayon::ThreadPoolBuilder::new()
+            .num_threads((cores / 2).max(1))
+            .thread_name(|index| format!("canary-pipeline-{index}"))
+            .build()
+            .expect("pipeline thread pool")
+    })
+}
+
+/// Processes a single page.
+pub fn process_page(url: &str, html: &str, opts: &Options) -> Result<Page, Error> {
+    let html_opts = html::Options {
+        url: Some(url.to_string())
+            .filter(|url| !url.is_empty())
+            .or_else(|| opts.html.url.clone()),
+        ..opts.html.clone()
+    };
+    let markdown = html::to_md(html, &html_opts)?.trim().to_string();
+    let chunks = chunk::chunk_markdown(&markdown, &opts.chunk)?;
+    let keywords = keywords::extract(&markdown, opts.max_keywords)?;
+
+    Ok(Page {
+        url: url.to_string(),
+        markdown,
+        chunks,
+        keywords,
+    })
+}
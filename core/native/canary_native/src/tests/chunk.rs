@@ -7,7 +7,8 @@ const MAX_TOKENS: usize = 20;
 
 #[test]
 fn chunk_text() {
-    let result = chunk::chunk_text(include_str_from_url!("https://raw.githubusercontent.com/BerriAI/litellm/b7eb2527ff5e21885e1886c825c3e97d53972bc8/docs/my-website/docs/completion/message_trimming.md"), MAX_TOKENS).unwrap();
+    let result = chunk::chunk_text(include_str_from_url!("https://raw.githubusercontent.com/BerriAI/litellm/b7eb2527ff5e21885e1886c825c3e97d53972bc8/docs/my-website/docs/completion/message_trimming.md"), &chunk::Options::new(MAX_TOKENS)).unwrap();
+    let result: Vec<String> = result.into_iter().map(|chunk| chunk.content).collect();
 
     assert_debug_snapshot!(result, @r###"
     [
@@ -37,25 +38,21 @@ fn chunk_text() {
 
 #[test]
 fn chunk_markdown() {
-    let result = chunk::chunk_markdown(include_str_from_url!("https://raw.githubusercontent.com/BerriAI/litellm/b7eb2527ff5e21885e1886c825c3e97d53972bc8/docs/my-website/docs/completion/message_trimming.md"), MAX_TOKENS).unwrap();
+    let result = chunk::chunk_markdown(include_str_from_url!("https://raw.githubusercontent.com/BerriAI/litellm/b7eb2527ff5e21885e1886c825c3e97d53972bc8/docs/my-website/docs/completion/message_trimming.md"), &chunk::Options::new(MAX_TOKENS)).unwrap();
+    let result: Vec<String> = result.into_iter().map(|chunk| chunk.content).collect();
 
     assert_debug_snapshot!(result, @r###"
     [
-        "# Trimming Input Messages",
-        "**Use litellm.trim_messages() to ensure messages does not exceed a model's token limit or",
+        "# Trimming Input Messages\n**Use litellm.trim_messages() to ensure messages does not exceed a model's token limit or",
         "specified `max_tokens`**",
-        "## Usage",
-        "```python",
-        "from litellm import completion\nfrom litellm.utils import trim_messages\n\nresponse = completion(",
-        "model=model,",
-        "messages=trim_messages(messages, model) # trim_messages ensures tokens(messages) < max_tokens(model)",
-        ") \n```",
-        "## Usage - set max_tokens",
-        "```python",
-        "from litellm import completion\nfrom litellm.utils import trim_messages\n\nresponse = completion(",
-        "model=model,",
-        "messages=trim_messages(messages, model, max_tokens=10), # trim_messages ensures tokens(messages)",
-        "< max_tokens\n) \n```",
+        "## Usage \n```python\nfrom litellm import completion\nfrom litellm.utils import trim_messages\n\nresponse = completion(\n```",
+        "```python\n    model=model,\n```",
+        "```python\n    messages=trim_messages(messages, model) # trim_messages ensures tokens(messages) < max_tokens(model)\n```",
+        "```python\n) \n```",
+        "## Usage - set max_tokens\n```python\nfrom litellm import completion\nfrom litellm.utils import trim_messages\n\nresponse = completion(\n```",
+        "```python\n    model=model,\n```",
+        "```python\n    messages=trim_messages(messages, model, max_tokens=10), # trim_messages ensures tokens(messages)\n```",
+        "```python\n< max_tokens\n) \n```",
         "## Parameters\n\nThe function uses the following parameters:",
         "- `messages`:[Required] This should be a list of input messages",
         "- `model`:[Optional]",
@@ -69,3 +66,339 @@ fn chunk_markdown() {
     ]
     "###);
 }
+
+#[test]
+fn splitter_reuse() {
+    let content = "# Title\n\nFirst paragraph.\n\n## Section\n\nSecond paragraph.";
+    let splitter = chunk::Splitter::markdown(&chunk::Options::new(MAX_TOKENS)).unwrap();
+
+    assert_eq!(
+        splitter.chunks(content).unwrap(),
+        splitter.chunks(content).unwrap()
+    );
+    assert_eq!(
+        splitter.chunks(content).unwrap(),
+        chunk::chunk_markdown(content, &chunk::Options::new(MAX_TOKENS)).unwrap()
+    );
+}
+
+#[test]
+fn chunk_markdown_metadata() {
+    let content = "# Trimming Input Messages\n\nIntro.\n\n## Usage\n\nCall `trim_messages`.";
+    let result = chunk::chunk_markdown(content, &chunk::Options::new(5)).unwrap();
+
+    for chunk in &result {
+        assert_eq!(&content[chunk.start..chunk.end], chunk.content);
+    }
+
+    let usage = result
+        .iter()
+        .find(|c| c.content.starts_with("## Usage"))
+        .unwrap();
+    assert_eq!(usage.content, "## Usage\n\nCall `trim_messages`.");
+    assert_eq!(usage.headings, vec!["Trimming Input Messages", "Usage"]);
+    assert_eq!(result[0].headings, vec!["Trimming Input Messages"]);
+}
+
+#[test]
+fn chunk_options() {
+    let content = "one two three four five six seven eight nine ten eleven twelve";
+    let opts = chunk::Options {
+        min_tokens: Some(4),
+        max_tokens: 8,
+        overlap_tokens: 2,
+        ..chunk::Options::default()
+    };
+    let result = chunk::chunk_text(content, &opts).unwrap();
+
+    assert!(result.len() > 1);
+    assert!(result.iter().all(|chunk| chunk.tokens <= 8));
+    assert!(result.windows(2).all(|w| w[1].start < w[0].end));
+
+    let invalid = chunk::Options {
+        overlap_tokens: 8,
+        ..chunk::Options::new(8)
+    };
+    let error = chunk::chunk_text(content, &invalid).unwrap_err();
+    assert_eq!(error.kind(), "invalid_options");
+    assert_eq!(
+        error.to_string(),
+        "invalid chunk options: overlap_tokens must be less than min_tokens (8), got 8"
+    );
+}
+
+#[test]
+fn merge_small_chunks() {
+    let content =
+        "# Title\n\nOne.\n\nTwo.\n\nThree.\n\nA much longer paragraph that is already big enough.";
+    let opts = chunk::Options {
+        min_tokens: Some(4),
+        merge_below_tokens: Some(6),
+        ..chunk::Options::new(16)
+    };
+    let unmerged = chunk::chunk_markdown(
+        content,
+        &chunk::Options {
+            merge_below_tokens: None,
+            ..opts.clone()
+        },
+    )
+    .unwrap();
+    let merged = chunk::chunk_markdown(content, &opts).unwrap();
+
+    assert!(merged.len() < unmerged.len());
+    assert!(merged.iter().all(|chunk| chunk.tokens <= 16));
+    assert_eq!(merged[0].start, 0);
+    assert_eq!(merged.last().unwrap().end, content.len());
+    for chunk in &merged {
+        assert_eq!(&content[chunk.start..chunk.end], chunk.content);
+    }
+}
+
+#[test]
+fn merge_small_chunks_counts_fences_and_headers() {
+    let lines: Vec<String> = (1..=12)
+        .map(|i| format!("    result_{i} = client.call(\"step_{i}\")"))
+        .collect();
+    let rows: Vec<String> = (1..=8)
+        .map(|i| format!("| `param_{i}` | Parameter number {i}. |"))
+        .collect();
+    let content = format!(
+        "Run the steps.\n\n```python\ndef run(client):\n{}\n```\n\n| Name | Description |\n| --- | --- |\n{}",
+        lines.join("\n"),
+        rows.join("\n")
+    );
+
+    for max_tokens in 20..60 {
+        let opts = chunk::Options {
+            merge_below_tokens: Some(max_tokens),
+            ..chunk::Options::new(max_tokens)
+        };
+        let unmerged = chunk::chunk_markdown(
+            &content,
+            &chunk::Options {
+                merge_below_tokens: None,
+                ..opts.clone()
+            },
+        )
+        .unwrap();
+        let merged = chunk::chunk_markdown(&content, &opts).unwrap();
+
+        // Merging never makes a chunk the splitter did not already overrun.
+        for chunk in &merged {
+            assert!(
+                chunk.tokens <= max_tokens || unmerged.contains(chunk),
+                "{max_tokens}: {} tokens in {:?}",
+                chunk.tokens,
+                chunk.content
+            );
+        }
+    }
+}
+
+#[test]
+fn contextual_header() {
+    let content = "# Trimming Input Messages\n\nIntro.\n\n## Parameters\n\n- `model`:[Optional] This is the LiteLLM model being used.";
+    let opts = chunk::Options {
+        header: Some(chunk::Header {
+            title: Some("LiteLLM".to_string()),
+            ..chunk::Header::default()
+        }),
+        ..chunk::Options::new(24)
+    };
+    let result = chunk::chunk_markdown(content, &opts).unwrap();
+
+    let last = result.last().unwrap();
+    assert_eq!(
+        last.text,
+        format!(
+            "LiteLLM > Trimming Input Messages > Parameters\n\n{}",
+            last.content
+        )
+    );
+    assert_eq!(&content[last.start..last.end], last.content);
+    assert!(result.iter().all(|chunk| chunk.tokens <= 24));
+
+    let plain = chunk::chunk_markdown(content, &chunk::Options::new(24)).unwrap();
+    assert!(plain.iter().all(|chunk| chunk.text == chunk.content));
+
+    let too_small = chunk::Options {
+        header: opts.header.clone(),
+        ..chunk::Options::new(8)
+    };
+    let error = chunk::chunk_markdown(content, &too_small).unwrap_err();
+    assert_eq!(error.kind(), "invalid_options");
+}
+
+#[test]
+fn chunk_hierarchical() {
+    let content = "# Guide\n\nIntro paragraph.\n\n## Install\n\nRun the installer. Then configure it.\n\n### Options\n\nSet the flags.\n\n## Usage\n\nCall the API.";
+    let opts = chunk::HierarchyOptions {
+        section_level: 2,
+        section: chunk::Options::new(200),
+        passage: chunk::Options::new(8),
+    };
+    let result = chunk::chunk_hierarchical(content, &opts).unwrap();
+
+    let sections: Vec<&str> = result
+        .iter()
+        .filter(|c| c.parent_id.is_none())
+        .map(|c| c.chunk.content.as_str())
+        .collect();
+    assert_eq!(
+        sections,
+        vec![
+            "# Guide\n\nIntro paragraph.",
+            "## Install\n\nRun the installer. Then configure it.\n\n### Options\n\nSet the flags.",
+            "## Usage\n\nCall the API.",
+        ]
+    );
+
+    for passage in result.iter().filter(|c| c.parent_id.is_some()) {
+        let parent = result
+            .iter()
+            .find(|c| Some(&c.id) == passage.parent_id.as_ref())
+            .unwrap();
+        assert!(parent.chunk.start <= passage.chunk.start && passage.chunk.end <= parent.chunk.end);
+    }
+
+    let options = result
+        .iter()
+        .find(|c| c.parent_id.is_some() && c.chunk.content.contains("Set the flags."))
+        .unwrap();
+    assert_eq!(options.chunk.headings, vec!["Guide", "Install", "Options"]);
+
+    let ids: std::collections::HashSet<&String> = result.iter().map(|c| &c.id).collect();
+    assert_eq!(ids.len(), result.len());
+    assert_eq!(chunk::chunk_hierarchical(content, &opts).unwrap(), result);
+}
+
+#[test]
+fn chunk_code() {
+    let content = r#"mod parser {
+    pub struct Parser;
+
+    impl Parser {
+        pub fn parse(&self, input: &str) -> usize {
+            input.len()
+        }
+
+        pub fn reset(&mut self) {}
+    }
+}
+"#;
+    let result =
+        chunk::chunk_code(content, "rust".parse().unwrap(), &chunk::Options::new(20)).unwrap();
+
+    for chunk in &result {
+        assert_eq!(&content[chunk.start..chunk.end], chunk.content);
+        assert!(chunk.tokens <= 20);
+    }
+    let parse = result
+        .iter()
+        .find(|c| c.content.starts_with("pub fn parse"))
+        .unwrap();
+    assert_eq!(parse.headings, vec!["parser", "impl Parser", "parse"]);
+
+    let content = r#"defmodule Canary.Reader do
+  def chunk_markdown(content, opts \\ []) when is_list(opts) do
+    Canary.Native.chunk_markdown(content, Map.new(opts))
+  end
+
+  defp tokenizer do
+    Application.get_env(:canary, :chunk_tokenizer)
+  end
+end
+"#;
+    let result =
+        chunk::chunk_code(content, "ex".parse().unwrap(), &chunk::Options::new(20)).unwrap();
+    let headings: Vec<&Vec<String>> = result.iter().map(|c| &c.headings).collect();
+    assert!(headings.contains(&&vec![
+        "Canary.Reader".to_string(),
+        "chunk_markdown".to_string()
+    ]));
+    assert!(headings.contains(&&vec!["Canary.Reader".to_string(), "tokenizer".to_string()]));
+
+    let error = "cobol".parse::<crate::code::Language>().unwrap_err();
+    assert_eq!(error.kind(), "unsupported_language");
+}
+
+#[test]
+fn chunk_markdown_table() {
+    let rows: Vec<String> = (1..=12)
+        .map(|i| format!("| `param_{i}` | string | Description of parameter number {i}. |"))
+        .collect();
+    let content = format!(
+        "## Parameters\n\n| Name | Type | Description |\n| --- | --- | --- |\n{}\n\nAfter the table.",
+        rows.join("\n")
+    );
+    let header = "| Name | Type | Description |\n| --- | --- | --- |\n";
+
+    // 60 tokens fit a few rows per chunk, 12 less than a single row.
+    for max_tokens in [60, 12] {
+        let result = chunk::chunk_markdown(&content, &chunk::Options::new(max_tokens)).unwrap();
+        let tables: Vec<&chunk::Chunk> = result
+            .iter()
+            .filter(|c| c.content.contains("param_"))
+            .collect();
+
+        assert!(tables.len() > 1);
+        assert!(tables[0].content.starts_with("## Parameters\n\n| Name"));
+        for chunk in &tables[1..] {
+            assert!(chunk.content.starts_with(&format!("{header}| `param_")));
+            assert_eq!(chunk.headings, vec!["Parameters"]);
+        }
+        for chunk in tables {
+            for line in chunk.content.lines().filter(|line| line.starts_with('|')) {
+                assert!(line.ends_with('|'), "cut row: {line:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn chunk_after_fence() {
+    let content = "```\na\n```\n\nnext paragraph of text goes here";
+    let result = chunk::chunk_markdown(content, &chunk::Options::new(3)).unwrap();
+    let texts: Vec<&str> = result.iter().map(|c| c.content.as_str()).collect();
+
+    // The chunk starting on the closing line is not re-opened as code.
+    assert_eq!(
+        texts,
+        vec!["```\na\n```", "next paragraph of", "text goes here"]
+    );
+}
+
+#[test]
+fn chunk_fence_in_admonition() {
+    let content = "Intro paragraph.\n\n> [!TIP]\n> Use the cache.\n>\n> ```python\n> print(1)\n> print(2)\n> ```\n\nAfter the tip.";
+    let result = chunk::chunk_markdown(content, &chunk::Options::new(8)).unwrap();
+    let texts: Vec<&str> = result.iter().map(|c| c.content.as_str()).collect();
+
+    // Re-opened and closed inside the quote, never on its own closing line.
+    assert_eq!(
+        texts,
+        vec![
+            "Intro paragraph.",
+            "> [!TIP]\n> Use the cache.\n>",
+            "> ```python\n> print(1)\n> ```",
+            "> ```python\n> print(2)\n> ```",
+            "After the tip.",
+        ]
+    );
+}
+
+#[test]
+fn chunk_alert_marker() {
+    let content = "> [!NOTE]\n> Caching is on by default and can be turned off per request.";
+
+    // From the smallest budget the marker fits in.
+    for max_tokens in 6..16 {
+        let result = chunk::chunk_markdown(content, &chunk::Options::new(max_tokens)).unwrap();
+        assert!(
+            result.iter().all(|c| c.content.trim() != "> [!NOTE]"),
+            "{max_tokens}: {result:?}"
+        );
+        assert!(result[0].content.starts_with("> [!NOTE]\n> Caching"));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::front_matter::{self, FrontMatter};
+
+#[test]
+fn yaml() {
+    let content = "---\ntitle: Getting Started\ndescription: Install and configure.\ntags: [setup, install]\nsidebar_position: 2\nslug: /start\nauthors: [jane]\n---\n\n# Getting Started\n\nRun the installer.";
+    let document = front_matter::parse(content);
+
+    assert_eq!(
+        document.front_matter,
+        FrontMatter {
+            title: Some("Getting Started".to_string()),
+            description: Some("Install and configure.".to_string()),
+            tags: vec!["setup".to_string(), "install".to_string()],
+            sidebar_position: Some(2.0),
+            slug: Some("/start".to_string()),
+            draft: false,
+        }
+    );
+    assert_eq!(document.body, "# Getting Started\n\nRun the installer.");
+    assert_eq!(&content[document.body_start..], document.body);
+}
+
+#[test]
+fn toml() {
+    let content =
+        "+++\ntitle = \"Release notes\"\ntags = [\"changelog\"]\ndraft = true\n+++\nNothing yet.";
+    let document = front_matter::parse(content);
+
+    assert_eq!(
+        document.front_matter.title.as_deref(),
+        Some("Release notes")
+    );
+    assert_eq!(document.front_matter.tags, vec!["changelog"]);
+    assert!(document.front_matter.draft);
+    assert_eq!(document.body, "Nothing yet.");
+}
+
+#[test]
+fn tag_forms() {
+    let jekyll = front_matter::parse("---\ntags: rust elixir\n---\n");
+    assert_eq!(jekyll.front_matter.tags, vec!["rust", "elixir"]);
+
+    let docusaurus = front_matter::parse(
+        "---\ntags:\n  - label: Guides\n    permalink: /guides\n  - api\n---\n",
+    );
+    assert_eq!(docusaurus.front_matter.tags, vec!["Guides", "api"]);
+
+    let empty = front_matter::parse("---\ntags:\n---\n");
+    assert!(empty.front_matter.tags.is_empty());
+}
+
+#[test]
+fn no_front_matter() {
+    for content in [
+        "# Title\n\n---\n\nBody",
+        "---\nnever closed",
+        "",
+        "---\ntitle: [unclosed\n---\n",
+        "---\n\nA paragraph after a thematic break.\n\n---\n\nAnother one.",
+        "---\n- a list\n---\n",
+        "+++\nnot = [toml\n+++\n",
+    ] {
+        let document = front_matter::parse(content);
+
+        assert_eq!(document.front_matter, FrontMatter::default());
+        assert_eq!(document.body, content);
+        assert_eq!(document.body_start, 0);
+    }
+}
+
+#[test]
+fn lenient_fields() {
+    let document = front_matter::parse(
+        "---\ntitle: 2024\ndescription: [not, a, string]\ntags: [1, 2, {label: Guides}, [nested]]\nsidebar_position: \"3\"\ndraft: yes\n---\nBody",
+    );
+
+    assert_eq!(
+        document.front_matter,
+        FrontMatter {
+            title: Some("2024".to_string()),
+            description: None,
+            tags: vec!["1".to_string(), "2".to_string(), "Guides".to_string()],
+            sidebar_position: Some(3.0),
+            slug: None,
+            draft: true,
+        }
+    );
+    assert_eq!(document.body, "Body");
+
+    let document = front_matter::parse(
+        "---\ntags: 42\nsidebar_position: first\ndraft: maybe\nslug: {}\n---\n",
+    );
+    assert_eq!(document.front_matter.tags, vec!["42"]);
+    assert_eq!(document.front_matter.sidebar_position, None);
+    assert!(!document.front_matter.draft);
+    assert_eq!(document.front_matter.slug, None);
+
+    let document = front_matter::parse("+++\ndraft = \"no\"\nsidebar_position = \"2.5\"\n+++\n");
+    assert!(!document.front_matter.draft);
+    assert_eq!(document.front_matter.sidebar_position, Some(2.5));
+}
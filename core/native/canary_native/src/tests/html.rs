@@ -1,18 +1,15 @@
 use crate::html;
 
-use include_uri::include_str_from_url;
 use insta::assert_snapshot;
 
 #[test]
 fn to_md() {
-    let html = include_str_from_url!("https://docs.litellm.ai");
-    let md = html::to_md(&html).unwrap();
+    let html = include_str!("fixtures/litellm.html");
+    let md = html::to_md(html, &html::Options::default()).unwrap();
     assert_snapshot!(md, @r###"
-    LiteLLM - Getting Started | liteLLM
-
     # LiteLLM - Getting Started
 
-    [https://github.com/BerriAI/litellm](https://github.com/BerriAI/litellm)
+    [https://github.com/BerriAI/litellm](https://github.com/BerriAI/litellm) 
 
     ## **Call 100+ LLMs using the OpenAI Input/Output Format**
 
@@ -30,11 +27,10 @@ fn to_md() {
 
     ### **When to use LiteLLM Proxy Server (LLM Gateway)**
 
-    tip
-
-    Use LiteLLM Proxy Server if you want a **central service (LLM Gateway) to access multiple LLMs**
-
-    Typically used by Gen AI Enablement / ML PLatform Teams
+    > [!TIP]
+    > Use LiteLLM Proxy Server if you want a **central service (LLM Gateway) to access multiple LLMs**
+    >
+    > Typically used by Gen AI Enablement / ML PLatform Teams
 
     *   LiteLLM Proxy gives you a unified interface to access multiple LLMs (100+ LLMs)
     *   Track LLM Usage and setup guardrails
@@ -42,11 +38,10 @@ fn to_md() {
 
     ### **When to use LiteLLM Python SDK**
 
-    tip
-
-    Use LiteLLM Python SDK if you want to use LiteLLM in your **python code**
-
-    Typically used by developers building llm projects
+    > [!TIP]
+    > Use LiteLLM Python SDK if you want to use LiteLLM in your **python code**
+    >
+    > Typically used by developers building llm projects
 
     *   LiteLLM SDK gives you a unified interface to access multiple LLMs (100+ LLMs)
     *   Retry/fallback logic across multiple deployments (e.g. Azure/OpenAI) - [Router](https://docs.litellm.ai/docs/routing)
@@ -58,303 +53,315 @@ fn to_md() {
     [![Open In Colab](https://colab.research.google.com/assets/colab-badge.svg)](https://colab.research.google.com/github/BerriAI/litellm/blob/main/cookbook/liteLLM_Getting_Started.ipynb)
 
     ```shell
-    pip install litellm  
+    pip install litellm
     ```
 
-    *   OpenAI
-    *   Anthropic
-    *   VertexAI
-    *   HuggingFace
-    *   Azure OpenAI
-    *   Ollama
-    *   Openrouter
+    **OpenAI**
 
     ```python
-    from litellm import completion  
-    import os  
-      
-    ## set ENV variables  
-    os.environ["OPENAI_API_KEY"] = "your-api-key"  
-      
-    response = completion(  
-      model="gpt-3.5-turbo",  
-      messages=[{ "content": "Hello, how are you?","role": "user"}]  
-    )  
+    from litellm import completion
+    import os
+
+    ## set ENV variables
+    os.environ["OPENAI_API_KEY"] = "your-api-key"
+
+    response = completion(
+      model="gpt-3.5-turbo",
+      messages=[{ "content": "Hello, how are you?","role": "user"}]
+    )
     ```
 
+    **Anthropic**
+
     ```python
-    from litellm import completion  
-    import os  
-      
-    ## set ENV variables  
-    os.environ["ANTHROPIC_API_KEY"] = "your-api-key"  
-      
-    response = completion(  
-      model="claude-2",  
-      messages=[{ "content": "Hello, how are you?","role": "user"}]  
-    )  
+    from litellm import completion
+    import os
+
+    ## set ENV variables
+    os.environ["ANTHROPIC_API_KEY"] = "your-api-key"
+
+    response = completion(
+      model="claude-2",
+      messages=[{ "content": "Hello, how are you?","role": "user"}]
+    )
     ```
 
+    **VertexAI**
+
     ```python
-    from litellm import completion  
-    import os  
-      
-    # auth: run 'gcloud auth application-default'  
-    os.environ["VERTEX_PROJECT"] = "hardy-device-386718"  
-    os.environ["VERTEX_LOCATION"] = "us-central1"  
-      
-    response = completion(  
-      model="chat-bison",  
-      messages=[{ "content": "Hello, how are you?","role": "user"}]  
-    )  
+    from litellm import completion
+    import os
+
+    # auth: run 'gcloud auth application-default'
+    os.environ["VERTEX_PROJECT"] = "hardy-device-386718"
+    os.environ["VERTEX_LOCATION"] = "us-central1"
+
+    response = completion(
+      model="chat-bison",
+      messages=[{ "content": "Hello, how are you?","role": "user"}]
+    )
     ```
 
+    **HuggingFace**
+
     ```python
-    from litellm import completion  
-    import os  
-      
-    os.environ["HUGGINGFACE_API_KEY"] = "huggingface_api_key"  
-      
-    # e.g. Call 'WizardLM/WizardCoder-Python-34B-V1.0' hosted on HF Inference endpoints  
-    response = completion(  
-      model="huggingface/WizardLM/WizardCoder-Python-34B-V1.0",  
-      messages=[{ "content": "Hello, how are you?","role": "user"}],  
-      api_base="https://my-endpoint.huggingface.cloud"  
-    )  
-      
-    print(response)  
+    from litellm import completion
+    import os
+
+    os.environ["HUGGINGFACE_API_KEY"] = "huggingface_api_key"
+
+    # e.g. Call 'WizardLM/WizardCoder-Python-34B-V1.0' hosted on HF Inference endpoints
+    response = completion(
+      model="huggingface/WizardLM/WizardCoder-Python-34B-V1.0",
+      messages=[{ "content": "Hello, how are you?","role": "user"}],
+      api_base="https://my-endpoint.huggingface.cloud"
+    )
+
+    print(response)
     ```
 
+    **Azure OpenAI**
+
     ```python
-    from litellm import completion  
-    import os  
-      
-    ## set ENV variables  
-    os.environ["AZURE_API_KEY"] = ""  
-    os.environ["AZURE_API_BASE"] = ""  
-    os.environ["AZURE_API_VERSION"] = ""  
-      
-    # azure call  
-    response = completion(  
-      "azure/<your_deployment_name>",  
-      messages = [{ "content": "Hello, how are you?","role": "user"}]  
-    )  
+    from litellm import completion
+    import os
+
+    ## set ENV variables
+    os.environ["AZURE_API_KEY"] = ""
+    os.environ["AZURE_API_BASE"] = ""
+    os.environ["AZURE_API_VERSION"] = ""
+
+    # azure call
+    response = completion(
+      "azure/<your_deployment_name>",
+      messages = [{ "content": "Hello, how are you?","role": "user"}]
+    )
     ```
 
+    **Ollama**
+
     ```python
-    from litellm import completion  
-      
-    response = completion(  
-                model="ollama/llama2",  
-                messages = [{ "content": "Hello, how are you?","role": "user"}],  
-                api_base="http://localhost:11434"  
-    )  
+    from litellm import completion
+
+    response = completion(
+                model="ollama/llama2",
+                messages = [{ "content": "Hello, how are you?","role": "user"}],
+                api_base="http://localhost:11434"
+    )
     ```
 
+    **Openrouter**
+
     ```python
-    from litellm import completion  
-    import os  
-      
-    ## set ENV variables  
-    os.environ["OPENROUTER_API_KEY"] = "openrouter_api_key"  
-      
-    response = completion(  
-      model="openrouter/google/palm-2-chat-bison",  
-      messages = [{ "content": "Hello, how are you?","role": "user"}],  
-    )  
+    from litellm import completion
+    import os
+
+    ## set ENV variables
+    os.environ["OPENROUTER_API_KEY"] = "openrouter_api_key"
+
+    response = completion(
+      model="openrouter/google/palm-2-chat-bison",
+      messages = [{ "content": "Hello, how are you?","role": "user"}],
+    )
     ```
 
     ### Streaming
 
     Set `stream=True` in the `completion` args. 
 
-    *   OpenAI
-    *   Anthropic
-    *   VertexAI
-    *   HuggingFace
-    *   Azure OpenAI
-    *   Ollama
-    *   Openrouter
+    **OpenAI**
 
     ```python
-    from litellm import completion  
-    import os  
-      
-    ## set ENV variables  
-    os.environ["OPENAI_API_KEY"] = "your-api-key"  
-      
-    response = completion(  
-      model="gpt-3.5-turbo",  
-      messages=[{ "content": "Hello, how are you?","role": "user"}],  
-      stream=True,  
-    )  
+    from litellm import completion
+    import os
+
+    ## set ENV variables
+    os.environ["OPENAI_API_KEY"] = "your-api-key"
+
+    response = completion(
+      model="gpt-3.5-turbo",
+      messages=[{ "content": "Hello, how are you?","role": "user"}],
+      stream=True,
+    )
     ```
 
+    **Anthropic**
+
     ```python
-    from litellm import completion  
-    import os  
-      
-    ## set ENV variables  
-    os.environ["ANTHROPIC_API_KEY"] = "your-api-key"  
-      
-    response = completion(  
-      model="claude-2",  
-      messages=[{ "content": "Hello, how are you?","role": "user"}],  
-      stream=True,  
-    )  
+    from litellm import completion
+    import os
+
+    ## set ENV variables
+    os.environ["ANTHROPIC_API_KEY"] = "your-api-key"
+
+    response = completion(
+      model="claude-2",
+      messages=[{ "content": "Hello, how are you?","role": "user"}],
+      stream=True,
+    )
     ```
 
+    **VertexAI**
+
     ```python
-    from litellm import completion  
-    import os  
-      
-    # auth: run 'gcloud auth application-default'  
-    os.environ["VERTEX_PROJECT"] = "hardy-device-386718"  
-    os.environ["VERTEX_LOCATION"] = "us-central1"  
-      
-    response = completion(  
-      model="chat-bison",  
-      messages=[{ "content": "Hello, how are you?","role": "user"}],  
-      stream=True,  
-    )  
+    from litellm import completion
+    import os
+
+    # auth: run 'gcloud auth application-default'
+    os.environ["VERTEX_PROJECT"] = "hardy-device-386718"
+    os.environ["VERTEX_LOCATION"] = "us-central1"
+
+    response = completion(
+      model="chat-bison",
+      messages=[{ "content": "Hello, how are you?","role": "user"}],
+      stream=True,
+    )
     ```
 
+    **HuggingFace**
+
     ```python
-    from litellm import completion  
-    import os  
-      
-    os.environ["HUGGINGFACE_API_KEY"] = "huggingface_api_key"  
-      
-    # e.g. Call 'WizardLM/WizardCoder-Python-34B-V1.0' hosted on HF Inference endpoints  
-    response = completion(  
-      model="huggingface/WizardLM/WizardCoder-Python-34B-V1.0",  
-      messages=[{ "content": "Hello, how are you?","role": "user"}],  
-      api_base="https://my-endpoint.huggingface.cloud",  
-      stream=True,  
-    )  
-      
-    print(response)  
+    from litellm import completion
+    import os
+
+    os.environ["HUGGINGFACE_API_KEY"] = "huggingface_api_key"
+
+    # e.g. Call 'WizardLM/WizardCoder-Python-34B-V1.0' hosted on HF Inference endpoints
+    response = completion(
+      model="huggingface/WizardLM/WizardCoder-Python-34B-V1.0",
+      messages=[{ "content": "Hello, how are you?","role": "user"}],
+      api_base="https://my-endpoint.huggingface.cloud",
+      stream=True,
+    )
+
+    print(response)
     ```
 
+    **Azure OpenAI**
+
     ```python
-    from litellm import completion  
-    import os  
-      
-    ## set ENV variables  
-    os.environ["AZURE_API_KEY"] = ""  
-    os.environ["AZURE_API_BASE"] = ""  
-    os.environ["AZURE_API_VERSION"] = ""  
-      
-    # azure call  
-    response = completion(  
-      "azure/<your_deployment_name>",  
-      messages = [{ "content": "Hello, how are you?","role": "user"}],  
-      stream=True,  
-    )  
+    from litellm import completion
+    import os
+
+    ## set ENV variables
+    os.environ["AZURE_API_KEY"] = ""
+    os.environ["AZURE_API_BASE"] = ""
+    os.environ["AZURE_API_VERSION"] = ""
+
+    # azure call
+    response = completion(
+      "azure/<your_deployment_name>",
+      messages = [{ "content": "Hello, how are you?","role": "user"}],
+      stream=True,
+    )
     ```
 
+    **Ollama**
+
     ```python
-    from litellm import completion  
-      
-    response = completion(  
-                model="ollama/llama2",  
-                messages = [{ "content": "Hello, how are you?","role": "user"}],  
-                api_base="http://localhost:11434",  
-                stream=True,  
-    )  
+    from litellm import completion
+
+    response = completion(
+                model="ollama/llama2",
+                messages = [{ "content": "Hello, how are you?","role": "user"}],
+                api_base="http://localhost:11434",
+                stream=True,
+    )
     ```
 
+    **Openrouter**
+
     ```python
-    from litellm import completion  
-    import os  
-      
-    ## set ENV variables  
-    os.environ["OPENROUTER_API_KEY"] = "openrouter_api_key"  
-      
-    response = completion(  
-      model="openrouter/google/palm-2-chat-bison",  
-      messages = [{ "content": "Hello, how are you?","role": "user"}],  
-      stream=True,  
-    )  
+    from litellm import completion
+    import os
+
+    ## set ENV variables
+    os.environ["OPENROUTER_API_KEY"] = "openrouter_api_key"
+
+    response = completion(
+      model="openrouter/google/palm-2-chat-bison",
+      messages = [{ "content": "Hello, how are you?","role": "user"}],
+      stream=True,
+    )
     ```
 
     ### Exception handling
 
-    LiteLLM maps exceptions across all supported providers to the OpenAI exceptions. All our exceptions inherit from OpenAI's exception types, so any error-handling you have for that, should work out of the box with LiteLLM.
+    LiteLLM maps exceptions across all supported providers to the OpenAI exceptions. All our exceptions inherit from OpenAI's exception types, so any error-handling you have for that, should work out of the box with LiteLLM. 
 
     ```python
-    from openai.error import OpenAIError  
-    from litellm import completion  
-      
-    os.environ["ANTHROPIC_API_KEY"] = "bad-key"  
-    try:  
-        # some code  
-        completion(model="claude-instant-1", messages=[{"role": "user", "content": "Hey, how's it going?"}])  
-    except OpenAIError as e:  
-        print(e)  
+    from openai.error import OpenAIError
+    from litellm import completion
+
+    os.environ["ANTHROPIC_API_KEY"] = "bad-key"
+    try:
+        # some code
+        completion(model="claude-instant-1", messages=[{"role": "user", "content": "Hey, how's it going?"}])
+    except OpenAIError as e:
+        print(e)
     ```
 
     ### Logging Observability - Log LLM Input/Output ([Docs](https://docs.litellm.ai/docs/observability/callbacks))
 
-    LiteLLM exposes pre defined callbacks to send data to Lunary, Langfuse, Helicone, Promptlayer, Traceloop, Slack
+    LiteLLM exposes pre defined callbacks to send data to Lunary, Langfuse, Helicone, Promptlayer, Traceloop, Slack 
 
     ```python
-    from litellm import completion  
-      
-    ## set env variables for logging tools  
-    os.environ["HELICONE_API_KEY"] = "your-helicone-key"  
-    os.environ["LANGFUSE_PUBLIC_KEY"] = ""  
-    os.environ["LANGFUSE_SECRET_KEY"] = ""  
-    os.environ["LUNARY_PUBLIC_KEY"] = "your-lunary-public-key"  
-      
-    os.environ["OPENAI_API_KEY"]  
-      
-    # set callbacks  
-    litellm.success_callback = ["lunary", "langfuse", "helicone"] # log input/output to lunary, langfuse, supabase, helicone  
-      
-    #openai call  
-    response = completion(model="gpt-3.5-turbo", messages=[{"role": "user", "content": "Hi 👋 - i'm openai"}])  
+    from litellm import completion
+
+    ## set env variables for logging tools
+    os.environ["HELICONE_API_KEY"] = "your-helicone-key"
+    os.environ["LANGFUSE_PUBLIC_KEY"] = ""
+    os.environ["LANGFUSE_SECRET_KEY"] = ""
+    os.environ["LUNARY_PUBLIC_KEY"] = "your-lunary-public-key"
+
+    os.environ["OPENAI_API_KEY"]
+
+    # set callbacks
+    litellm.success_callback = ["lunary", "langfuse", "helicone"] # log input/output to lunary, langfuse, supabase, helicone
+
+    #openai call
+    response = completion(model="gpt-3.5-turbo", messages=[{"role": "user", "content": "Hi 👋 - i'm openai"}])
     ```
 
     ### Track Costs, Usage, Latency for streaming
 
-    Use a callback function for this - more info on custom callbacks: [https://docs.litellm.ai/docs/observability/custom\_callback](https://docs.litellm.ai/docs/observability/custom_callback)
+    Use a callback function for this - more info on custom callbacks: [https://docs.litellm.ai/docs/observability/custom\_callback](https://docs.litellm.ai/docs/observability/custom_callback) 
 
     ```python
-    import litellm  
-      
-    # track_cost_callback  
-    def track_cost_callback(  
-        kwargs,                 # kwargs to completion  
-        completion_response,    # response from completion  
-        start_time, end_time    # start/end time  
-    ):  
-        try:  
-          response_cost = kwargs.get("response_cost", 0)  
-          print("streaming response_cost", response_cost)  
-        except:  
-            pass  
-    # set callback  
-    litellm.success_callback = [track_cost_callback] # set custom callback function  
-      
-    # litellm.completion() call  
-    response = completion(  
-        model="gpt-3.5-turbo",  
-        messages=[  
-            {  
-                "role": "user",  
-                "content": "Hi 👋 - i'm openai"  
-            }  
-        ],  
-        stream=True  
-    )  
+    import litellm
+
+    # track_cost_callback
+    def track_cost_callback(
+        kwargs,                 # kwargs to completion
+        completion_response,    # response from completion
+        start_time, end_time    # start/end time
+    ):
+        try:
+          response_cost = kwargs.get("response_cost", 0)
+          print("streaming response_cost", response_cost)
+        except:
+            pass
+    # set callback
+    litellm.success_callback = [track_cost_callback] # set custom callback function
+
+    # litellm.completion() call
+    response = completion(
+        model="gpt-3.5-turbo",
+        messages=[
+            {
+                "role": "user",
+                "content": "Hi 👋 - i'm openai"
+            }
+        ],
+        stream=True
+    )
     ```
 
     ## **LiteLLM Proxy Server (LLM Gateway)**
 
     Track spend across multiple projects/people
 
-    ![ui_3](https://github.com/BerriAI/litellm/assets/29436595/47c97d5e-b9be-4839-b28c-43d7f4f10033)
+    ![ui_3](https://github.com/BerriAI/litellm/assets/29436595/47c97d5e-b9be-4839-b28c-43d7f4f10033) 
 
     The proxy provides:
 
@@ -365,86 +372,44 @@ fn to_md() {
 
     ### 📖 Proxy Endpoints - [Swagger Docs](https://litellm-api.up.railway.app/)
 
-    Go here for a complete tutorial with keys + rate limits - [**here**](/proxy/docker_quick_start.md)
+    Go here for a complete tutorial with keys + rate limits - [**here**](/docs/proxy/docker_quick_start) 
 
     ### Quick Start Proxy - CLI
 
     ```shell
-    pip install 'litellm[proxy]'  
+    pip install 'litellm[proxy]'
     ```
 
     #### Step 1: Start litellm proxy
 
-    *   pip package
-    *   Docker container
-
     ```shell
-    $ litellm --model huggingface/bigcode/starcoder  
-      
-    #INFO: Proxy running on http://0.0.0.0:4000  
-    ```
-
-    ### Step 1. CREATE config.yaml
-
-    Example `litellm_config.yaml` 
-
-    ```yaml
-    model_list:  
-      - model_name: gpt-3.5-turbo  
-        litellm_params:  
-          model: azure/<your-azure-model-deployment>  
-          api_base: os.environ/AZURE_API_BASE # runs os.getenv("AZURE_API_BASE")  
-          api_key: os.environ/AZURE_API_KEY # runs os.getenv("AZURE_API_KEY")  
-          api_version: "2023-07-01-preview"  
-    ```
+    $ litellm --model huggingface/bigcode/starcoder
 
-    ### Step 2. RUN Docker Image
-
-    ```shell
-    docker run \  
-        -v $(pwd)/litellm_config.yaml:/app/config.yaml \  
-        -e AZURE_API_KEY=d6*********** \  
-        -e AZURE_API_BASE=https://openai-***********/ \  
-        -p 4000:4000 \  
-        ghcr.io/berriai/litellm:main-latest \  
-        --config /app/config.yaml --detailed_debug  
+    #INFO: Proxy running on http://0.0.0.0:4000
     ```
 
     #### Step 2: Make ChatCompletions Request to Proxy
 
     ```python
-    import openai # openai v1.0.0+  
-    client = openai.OpenAI(api_key="anything",base_url="http://0.0.0.0:4000") # set proxy to base_url  
-    # request sent to model set on litellm proxy, `litellm --model`  
-    response = client.chat.completions.create(model="gpt-3.5-turbo", messages = [  
-        {  
-            "role": "user",  
-            "content": "this is a test request, write a short poem"  
-        }  
-    ])  
-      
-    print(response)  
+    import openai # openai v1.0.0+
+    client = openai.OpenAI(api_key="anything",base_url="http://0.0.0.0:4000") # set proxy to base_url
+    # request sent to model set on litellm proxy, `litellm --model`
+    response = client.chat.completions.create(model="gpt-3.5-turbo", messages = [
+        {
+            "role": "user",
+            "content": "this is a test request, write a short poem"
+        }
+    ])
+
+    print(response)
     ```
 
     ## More details
 
     *   [exception mapping](/docs/exception_mapping)
-    *   [E2E Tutorial for LiteLLM Proxy Server](/docs/proxy/docker_quick_start)
+    *   [retries + model fallbacks for completion()](/docs/completion/reliable_completions)
     *   [proxy virtual keys & spend management](/docs/proxy/virtual_keys)
-
-    *   
-    *   *
-        *
-    *   *
-        *
-        *
-        *
-        *
-    *   *
-        *
-        *
-        *
-    *
+    *   [E2E Tutorial for LiteLLM Proxy Server](/docs/proxy/docker_quick_start)
     "###);
 }
 
@@ -460,18 +425,789 @@ fn to_md_vitepress_code() {
 <span class="line"><span style="--shiki-light:#24292E;--shiki-dark:#E1E4E8;">&lt;/</span><span style="--shiki-light:#22863A;--shiki-dark:#85E89D;">canary-root</span><span style="--shiki-light:#24292E;--shiki-dark:#E1E4E8;">&gt;</span></span></code></pre></div>
     "#;
 
-    let md = html::to_md(&html).unwrap();
+    let md = html::to_md(html, &html::Options::default()).unwrap();
     assert_snapshot!(md, @r###"
-    html
-
-    ```
+    ```html
     <canary-root framework="vitepress">
-      <canary-provider-vitepress-minisearch>
-        <canary-provider-cloud api-key="KEY" api-base="https://cloud.getcanary.dev">
+    -  <canary-provider-vitepress-minisearch>
+    +    <canary-provider-cloud api-key="KEY" api-base="https://cloud.getcanary.dev">
           <!-- Rest of the code -->
-        </canary-provider-cloud>
-      </canary-provider-vitepress-minisearch>
+    +    </canary-provider-cloud>
+    -  </canary-provider-vitepress-minisearch>
     </canary-root>
     ```
     "###);
 }
+
+#[test]
+fn to_md_code_languages() {
+    let html = r#"<html><body>
+<div class="language-js theme-code-block"><div class="codeBlockContent"><pre class="prism-code"><code><span class="token-line"><span class="codeLineNumber">1</span>const x = 1;<br></span><span class="token-line"><span class="codeLineNumber">2</span>x + 1;<br></span></code></pre><div class="buttonGroup"><button aria-label="Copy code to clipboard">Copy</button></div></div></div>
+<div class="highlight-source-rust"><pre>fn main() {}</pre></div>
+<div class="highlight-python notranslate"><table class="highlighttable"><tbody><tr><td class="linenos"><div class="linenodiv"><pre>1
+2</pre></div></td><td class="code"><div class="highlight"><pre><span class="kn">import</span> os
+<span class="nb">print</span>(os.getcwd())</pre></div></td></tr></tbody></table></div>
+<pre data-language="Elixir"><code>IO.puts("hi")</code></pre>
+<pre class="line-numbers language-none"><code class="language-none">plain<span class="line-numbers-rows"><span></span></span></code></pre>
+</body></html>"#;
+
+    let md = html::to_md(html, &html::Options::default()).unwrap();
+    assert_snapshot!(md, @r###"
+    ```js
+    const x = 1;
+    x + 1;
+    ```
+
+    ```rust
+    fn main() {}
+    ```
+
+    ```python
+    import os
+    print(os.getcwd())
+    ```
+
+    ```elixir
+    IO.puts("hi")
+    ```
+
+    ```
+    plain
+    ```
+    "###);
+}
+
+#[test]
+fn to_md_code_languages_ignore_page_lang() {
+    let html = r#"<html data-lang="en"><body>
+<div class="lang-en"><h1>Title</h1><p>Intro</p><pre><code>let x = 1;</code></pre><p>Closing</p></div>
+<div class="highlight-box"><pre>plain</pre></div>
+</body></html>"#;
+
+    let md = html::to_md(html, &html::Options::default()).unwrap();
+    assert_snapshot!(md, @r###"
+    # Title
+
+    Intro
+
+    ```
+    let x = 1;
+    ```
+
+    Closing
+
+    ```
+    plain
+    ```
+    "###);
+}
+
+#[test]
+fn to_md_code_toolbar() {
+    let html = r#"<html><body><div class="content">
+<p>Install it:</p>
+<div class="language-sh"><button title="Copy Code" class="copy"></button><span class="lang">sh</span><pre><code>npm i acme</code></pre></div>
+<div class="language-py"><div class="relative"><pre><code>import acme</code></pre></div><div class="absolute"><button aria-label="Copy"><svg></svg></button><div class="hidden">Copied!</div></div></div>
+<p>Then import it.</p>
+</div></body></html>"#;
+
+    let md = html::to_md(html, &html::Options::default()).unwrap();
+    assert_snapshot!(md, @r###"
+    Install it:
+
+    ```sh
+    npm i acme
+    ```
+
+    ```py
+    import acme
+    ```
+
+    Then import it.
+    "###);
+}
+
+#[test]
+fn to_md_admonitions() {
+    let html = r#"<html><body>
+<div class="markdown-alert markdown-alert-important" dir="auto"><p class="markdown-alert-title" dir="auto"><svg class="octicon"><path d="M0"></path></svg>Important</p><p dir="auto">Back up first.</p></div>
+<div class="theme-admonition theme-admonition-tip admonition_xJq3 alert alert--success"><div class="admonitionHeading_Gvgb"><span class="admonitionIcon_Rf37"><svg></svg></span>Pro tip</div><div class="admonitionContent_BuS1"><p>Use the cache.</p><p>It is fast.</p></div></div>
+<div class="danger custom-block"><p class="custom-block-title">DANGER</p><p>Do not run this in production.</p></div>
+<div class="admonition seealso"><p class="admonition-title">See also</p><p>The API reference.</p></div>
+<div class="admonition example"><p class="admonition-title">Example</p><p>Call it twice.</p></div>
+<details class="details custom-block"><summary>Show output</summary><pre><code>ok</code></pre></details>
+<blockquote><p>Just a quote.</p></blockquote>
+</body></html>"#;
+
+    let md = html::to_md(html, &html::Options::default()).unwrap();
+    assert_snapshot!(md, @r###"
+    > [!IMPORTANT]
+    > Back up first.
+
+    > [!TIP]
+    > **Pro tip**
+    >
+    > Use the cache.
+    >
+    > It is fast.
+
+    > [!CAUTION]
+    > Do not run this in production.
+
+    > **See also:**
+    >
+    > The API reference.
+
+    > **Example:**
+    >
+    > Call it twice.
+
+    > **Show output:**
+    >
+    > ```
+    > ok
+    > ```
+
+    > Just a quote.
+    "###);
+}
+
+#[test]
+fn to_md_admonitions_need_callout_markup() {
+    let html = r#"<html><body>
+<p>Enter your email <span class="hint">(we never share it)</span> below.</p>
+<p class="callout">A pull quote.</p>
+<div class="callout">Promoted content.</div>
+<div class="admonition"><p>An admonition-like wrapper.</p></div>
+</body></html>"#;
+
+    let md = html::to_md(html, &html::Options::default()).unwrap();
+    assert_snapshot!(md, @r###"
+    Enter your email (we never share it) below.
+
+    A pull quote.
+
+    Promoted content.
+
+    An admonition-like wrapper.
+    "###);
+}
+
+#[test]
+fn main_content() {
+    let html = r##"<!doctype html>
+<html>
+<head><title>Routing | Docs</title></head>
+<body>
+  <div class="announcementBar" role="banner">We are hiring!</div>
+  <div class="navbar"><a href="/">Home</a> <a href="/docs">Docs</a> <a href="/blog">Blog</a></div>
+  <div class="container">
+    <div class="theme-doc-sidebar-container">
+      <ul><li><a href="/docs/intro">Introduction</a></li><li><a href="/docs/routing">Routing</a></li></ul>
+    </div>
+    <div class="row">
+      <div class="col">
+        <div class="theme-doc-markdown markdown">
+          <h1>Routing</h1>
+          <p>The router picks a deployment for every request, based on latency, cost and the fallbacks you configure.</p>
+          <p>Deployments that fail are put on cooldown, and requests are retried on the next healthy one.</p>
+          <ul class="table-of-contents"><li><a href="#fallbacks">Fallbacks</a></li></ul>
+        </div>
+        <div class="theme-doc-footer-edit-meta-row"><a href="https://github.com/x/y/edit/main/routing.md">Edit this page</a></div>
+        <div class="pagination-nav__item"><a href="/docs/intro">Previous: Introduction</a> <a href="/docs/proxy">Next: Proxy</a></div>
+      </div>
+    </div>
+  </div>
+  <div id="cookie-consent">We use cookies to improve your experience on this website.</div>
+</body>
+</html>"##;
+
+    let md = html::to_md(
+        html,
+        &html::Options {
+            main_content: true,
+            ..html::Options::default()
+        },
+    )
+    .unwrap();
+    assert_snapshot!(md, @r###"
+    # Routing
+
+    The router picks a deployment for every request, based on latency, cost and the fallbacks you configure.
+
+    Deployments that fail are put on cooldown, and requests are retried on the next healthy one.
+    "###);
+
+    let full = html::to_md(html, &html::Options::default()).unwrap();
+    assert!(full.contains("Edit this page"));
+}
+
+#[test]
+fn main_content_names_inside_article() {
+    let html = r##"<html><body>
+<div class="sidebar"><p>Browse every guide in the documentation from this sidebar, sorted by topic.</p></div>
+<article>
+  <h1>Menus</h1>
+  <p>Menus group the actions of a page, and every item can run a command or open a submenu.</p>
+  <div class="menu-example"><p>A menu with three items, the second of which opens a submenu.</p></div>
+  <div id="share-settings"><p>Sharing settings decide who else can open a menu you created.</p></div>
+  <nav><a href="/docs/menus/items">Items</a></nav>
+</article>
+</body></html>"##;
+
+    let md = html::to_md(
+        html,
+        &html::Options {
+            main_content: true,
+            ..html::Options::default()
+        },
+    )
+    .unwrap();
+    assert_snapshot!(md, @r###"
+    # Menus
+
+    Menus group the actions of a page, and every item can run a command or open a submenu.
+
+    A menu with three items, the second of which opens a submenu.
+
+    Sharing settings decide who else can open a menu you created.
+    "###);
+}
+
+#[test]
+fn select_precedence() {
+    let html = r#"<html><body><div id="__docusaurus">
+<div class="intro"><p>Read this first, it explains how every later page is laid out.</p></div>
+<div class="theme-doc-markdown"><h1>Proxy</h1><p>Start the proxy.</p></div>
+</div></body></html>"#;
+    let to_md = |opts: html::Options| html::to_md(html, &opts).unwrap();
+
+    let preset = to_md(html::Options {
+        main_content: true,
+        ..html::Options::default()
+    });
+    assert_eq!(preset, "# Proxy\n\nStart the proxy.");
+
+    let included = to_md(html::Options {
+        main_content: true,
+        include: vec![".intro".to_string()],
+        preset: Some(html::Preset::Docusaurus),
+        ..html::Options::default()
+    });
+    assert_eq!(
+        included,
+        "Read this first, it explains how every later page is laid out."
+    );
+}
+
+#[test]
+fn selectors() {
+    let html = r#"<html><body>
+<div class="theme-doc-toc-mobile"><a href="/docs/a">On this page</a></div>
+<article class="markdown"><h1>Proxy</h1><p>Start the proxy.</p><div class="admonition">Needs a key.</div></article>
+<nav class="pagination-nav"><a href="/docs/b">Next</a></nav>
+<div class="footer-links">Community</div>
+</body></html>"#;
+
+    let md = html::to_md(html, &html::Options::default()).unwrap();
+    assert!(!md.contains("On this page"));
+    assert!(md.contains("Community"));
+
+    let opts = html::Options {
+        include: vec!["article.markdown".to_string(), "article h1".to_string()],
+        exclude: vec![".admonition".to_string()],
+        ..html::Options::default()
+    };
+    let md = html::to_md(html, &opts).unwrap();
+    assert_eq!(md, "# Proxy\n\nStart the proxy.");
+
+    let invalid = html::Options {
+        exclude: vec!["div[".to_string()],
+        ..html::Options::default()
+    };
+    let error = html::to_md(html, &invalid).unwrap_err();
+    assert_eq!(error.kind(), "selector");
+}
+
+#[test]
+fn absolute_links() {
+    let html = r##"<html><head><base href="/docs/"></head><body>
+<p>See the <a href="../routing">Router</a>, the <a href="proxy#setup">proxy</a>, <a href="#usage">usage</a> and <a href="https://github.com/BerriAI/litellm">GitHub</a>.</p>
+<p><img src="/img/logo.png" alt="Logo"> <a href="javascript:void(0)">Copy</a></p>
+</body></html>"##;
+    let opts = html::Options {
+        url: Some("https://docs.litellm.ai/docs/completion/input".to_string()),
+        ..html::Options::default()
+    };
+
+    let md = html::to_md(html, &opts).unwrap();
+    assert_snapshot!(md, @r###"
+    See the [Router](https://docs.litellm.ai/routing), the [proxy](https://docs.litellm.ai/docs/proxy#setup),  and [GitHub](https://github.com/BerriAI/litellm).
+
+    ![Logo](https://docs.litellm.ai/img/logo.png) Copy
+    "###);
+
+    let same_origin = html::to_md(
+        html,
+        &html::Options {
+            drop_external_links: true,
+            ..opts
+        },
+    )
+    .unwrap();
+    assert!(same_origin.contains("and GitHub."));
+
+    let invalid = html::Options {
+        url: Some("not a url".to_string()),
+        ..html::Options::default()
+    };
+    assert_eq!(html::to_md(html, &invalid).unwrap_err().kind(), "url");
+}
+
+#[test]
+fn page_meta() {
+    let html = r##"<!DOCTYPE html><html lang="en"><head>
+<title>
+  Input Params | liteLLM
+</title>
+<meta name="description" content="Supported input params for completion()">
+<meta name="generator" content="Docusaurus v3.4.0">
+<meta name="robots" content="NoIndex, follow">
+<meta property="og:title" content="Input Params">
+<meta property="og:image" content="/img/social-card.png">
+<meta name="twitter:card" content="summary_large_image">
+<link rel="canonical" href="/docs/completion/input">
+<link rel="alternate" hreflang="ja" href="/ja/docs/completion/input">
+<link rel="alternate" type="application/rss+xml" href="/blog/rss.xml">
+</head><body><h1>Input Params</h1></body></html>"##;
+
+    let meta = html::page_meta(html, "https://docs.litellm.ai/docs/completion/input/").unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Input Params | liteLLM"));
+    assert_eq!(
+        meta.description.as_deref(),
+        Some("Supported input params for completion()")
+    );
+    assert_eq!(
+        meta.canonical.as_deref(),
+        Some("https://docs.litellm.ai/docs/completion/input")
+    );
+    assert_eq!(meta.lang.as_deref(), Some("en"));
+    assert_eq!(meta.generator.as_deref(), Some("Docusaurus v3.4.0"));
+    assert_eq!(meta.open_graph["title"], "Input Params");
+    assert_eq!(
+        meta.open_graph["image"],
+        "https://docs.litellm.ai/img/social-card.png"
+    );
+    assert_eq!(meta.twitter["card"], "summary_large_image");
+    assert_eq!(meta.robots, vec!["noindex", "follow"]);
+    assert!(meta.noindex());
+    assert_eq!(
+        meta.alternates,
+        vec![html::Alternate {
+            hreflang: "ja".to_string(),
+            href: "https://docs.litellm.ai/ja/docs/completion/input".to_string(),
+        }]
+    );
+
+    let relative = html::page_meta(html, "").unwrap();
+    assert_eq!(
+        relative.canonical.as_deref(),
+        Some("/docs/completion/input")
+    );
+
+    let empty = html::page_meta("<p>Hello</p>", "").unwrap();
+    assert_eq!(empty, html::PageMeta::default());
+    assert!(!empty.noindex());
+}
+
+#[test]
+fn sections() {
+    let html = r##"<html><body><nav><a href="/">Home</a></nav>
+<article><header><h1 id="guide">Guía de inicio<a class="hash-link" href="#guide">&#8203;</a></h1></header>
+<p>Intro before any section, in <code>Canary</code>.</p>
+<h2 id="install">Instalación <a class="header-anchor" href="#install">#</a></h2>
+<p>Ejecuta el comando — funciona en 日本語 también.</p>
+<h3>Sin ancla</h3>
+</article></body></html>"##;
+
+    let sections = html::to_sections(html, &html::Options::default()).unwrap();
+    assert_eq!(
+        sections,
+        vec![
+            html::Section {
+                id: Some("guide".to_string()),
+                level: 1,
+                title: "Guía de inicio".to_string(),
+                content: "# Guía de inicio\n\nIntro before any section, in `Canary`.".to_string(),
+            },
+            html::Section {
+                id: Some("install".to_string()),
+                level: 2,
+                title: "Instalación".to_string(),
+                content: "## Instalación\n\nEjecuta el comando — funciona en 日本語 también."
+                    .to_string(),
+            },
+            html::Section {
+                id: None,
+                level: 3,
+                title: "Sin ancla".to_string(),
+                content: "### Sin ancla".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn page() {
+    let html = r##"<html><head>
+<title>Routing</title>
+<meta name="robots" content="noindex">
+<link rel="canonical" href="/docs/routing">
+</head><body><article><h1 id="routing">Routing</h1><p>Pick a deployment.</p></article></body></html>"##;
+    let opts = html::Options {
+        url: Some("https://docs.litellm.ai/docs/routing/".to_string()),
+        ..html::Options::default()
+    };
+
+    let page = html::to_page(html, &opts).unwrap();
+    assert_eq!(
+        page.meta,
+        html::page_meta(html, opts.url.as_deref().unwrap()).unwrap()
+    );
+    assert!(page.meta.noindex());
+    assert_eq!(
+        page.meta.canonical.as_deref(),
+        Some("https://docs.litellm.ai/docs/routing")
+    );
+    assert_eq!(page.sections, html::to_sections(html, &opts).unwrap());
+}
+
+#[test]
+fn links() {
+    let html = r##"<html><head>
+<link rel="canonical" href="/docs/intro">
+<link rel="stylesheet" href="/styles.css">
+<link rel="next" href="/docs/install">
+</head><body>
+<a href="/docs/intro#usage">Usage</a>
+<a href="install"><img src="/img/next.svg" alt="Next page"></a>
+<a href="install?tab=npm">npm</a>
+<a href="/blog/2024" rel="nofollow">Blog</a>
+<a href="https://github.com/fastrepl/canary" rel="noopener">GitHub</a>
+<a href="/docs/api/private/keys">Keys</a>
+<a href="mailto:hi@getcanary.dev">Mail</a>
+<a href="#top">Top</a>
+</body></html>"##;
+    let url = "https://getcanary.dev/docs/";
+
+    let links = html::links(html, url, &html::LinkFilter::default()).unwrap();
+    let summary: Vec<_> = links
+        .iter()
+        .map(|link| {
+            format!(
+                "{} {:?} {:?} {}",
+                link.url, link.text, link.rel, link.same_origin
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            r#"https://getcanary.dev/docs/intro "Usage" ["canonical"] true"#,
+            r#"https://getcanary.dev/docs/install "Next page" ["next"] true"#,
+            r#"https://getcanary.dev/docs/install?tab=npm "npm" [] true"#,
+            r#"https://getcanary.dev/blog/2024 "Blog" ["nofollow"] true"#,
+            r#"https://github.com/fastrepl/canary "GitHub" ["noopener"] false"#,
+            r#"https://getcanary.dev/docs/api/private/keys "Keys" [] true"#,
+            r#"https://getcanary.dev/docs/ "Top" [] true"#,
+        ]
+    );
+    assert!(links[3].nofollow());
+
+    let filter = html::LinkFilter {
+        include: vec!["https://getcanary.dev/docs/**".to_string()],
+        exclude: vec!["**/private/**".to_string()],
+    };
+    let urls: Vec<_> = html::links(html, url, &filter)
+        .unwrap()
+        .into_iter()
+        .map(|link| link.url)
+        .collect();
+    assert_eq!(
+        urls,
+        vec![
+            "https://getcanary.dev/docs/intro",
+            "https://getcanary.dev/docs/install",
+            "https://getcanary.dev/docs/install?tab=npm",
+            "https://getcanary.dev/docs/",
+        ]
+    );
+
+    let filter = html::LinkFilter {
+        include: vec!["**/install".to_string()],
+        exclude: vec![],
+    };
+    let urls: Vec<_> = html::links(html, url, &filter)
+        .unwrap()
+        .into_iter()
+        .map(|link| link.url)
+        .collect();
+    assert_eq!(
+        urls,
+        vec![
+            "https://getcanary.dev/docs/install",
+            "https://getcanary.dev/docs/install?tab=npm",
+        ]
+    );
+}
+
+#[test]
+fn links_same_origin_with_base() {
+    let html = r#"<html><head><base href="https://cdn.example.com/assets/"></head><body>
+<a href="page">Asset</a>
+<a href="https://getcanary.dev/docs/">Docs</a>
+<a href="https://getcanary.dev/docs/">Docs again</a>
+</body></html>"#;
+
+    let links = html::links(
+        html,
+        "https://getcanary.dev/blog/",
+        &html::LinkFilter::default(),
+    )
+    .unwrap();
+    let summary: Vec<_> = links
+        .iter()
+        .map(|link| format!("{} {:?} {}", link.url, link.text, link.same_origin))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            r#"https://cdn.example.com/assets/page "Asset" false"#,
+            r#"https://getcanary.dev/docs/ "Docs" true"#,
+        ]
+    );
+
+    let links = html::links(html, "", &html::LinkFilter::default()).unwrap();
+    assert!(links.iter().all(|link| !link.same_origin));
+}
+
+/// Converts a stored page with the preset detected from it.
+fn preset_fixture(content: &str) -> String {
+    html::to_md(content, &html::Options::default()).unwrap()
+}
+
+#[test]
+fn preset_detection() {
+    let fixtures = [
+        (
+            include_str!("fixtures/docusaurus.html"),
+            html::Preset::Docusaurus,
+        ),
+        (
+            include_str!("fixtures/vitepress.html"),
+            html::Preset::Vitepress,
+        ),
+        (include_str!("fixtures/mkdocs.html"), html::Preset::Mkdocs),
+        (include_str!("fixtures/sphinx.html"), html::Preset::Sphinx),
+        (include_str!("fixtures/gitbook.html"), html::Preset::Gitbook),
+        (include_str!("fixtures/nextra.html"), html::Preset::Nextra),
+        (
+            include_str!("fixtures/mintlify.html"),
+            html::Preset::Mintlify,
+        ),
+    ];
+    for (content, preset) in fixtures {
+        let document = scraper::Html::parse_document(content);
+        assert_eq!(html::Preset::detect(&document), Some(preset));
+    }
+
+    assert_eq!(
+        "MkDocs".parse::<html::Preset>().unwrap(),
+        html::Preset::Mkdocs
+    );
+    assert_eq!("hugo".parse::<html::Preset>().unwrap_err().kind(), "preset");
+}
+
+#[test]
+fn preset_docusaurus() {
+    let md = preset_fixture(include_str!("fixtures/docusaurus.html"));
+    assert_snapshot!(md, @r###"
+    # Installation
+
+    Acme runs anywhere Node.js 18 or later is available.
+
+    ## Install the package
+
+    **npm**
+
+    ```bash
+    npm install @acme/sdk
+    ```
+
+    **Yarn**
+
+    ```bash
+    yarn add @acme/sdk
+    ```
+
+    > [!TIP]
+    > Pin the version in CI.
+    "###);
+}
+
+#[test]
+fn preset_vitepress() {
+    let md = preset_fixture(include_str!("fixtures/vitepress.html"));
+    assert_snapshot!(md, @r###"
+    # Getting Started
+
+    Acme is a small, fast web framework.
+
+    ## Install
+
+    **npm**
+
+    ```sh
+    npm install acme
+    ```
+
+    **pnpm**
+
+    ```sh
+    pnpm add acme
+    ```
+
+    > [!WARNING]
+    > Node.js 16 is no longer supported.
+    "###);
+}
+
+#[test]
+fn preset_mkdocs() {
+    let md = preset_fixture(include_str!("fixtures/mkdocs.html"));
+    assert_snapshot!(md, @r###"
+    # Configuration
+
+    Acme reads its settings from `acme.yml`.
+
+    ## Environment
+
+    **Linux**
+
+    ```bash
+    export ACME_ENV=production
+    ```
+
+    **Windows**
+
+    ```powershell
+    $Env:ACME_ENV = "production"
+    ```
+
+    > [!NOTE]
+    > Environment variables override `acme.yml`.
+    "###);
+}
+
+#[test]
+fn preset_sphinx() {
+    let md = preset_fixture(include_str!("fixtures/sphinx.html"));
+    assert_snapshot!(md, @r###"
+    # Quickstart
+
+    Install acme and open a connection.
+
+    **pip**
+
+    ```console
+    $ pip install acme
+    ```
+
+    **conda**
+
+    ```console
+    $ conda install acme
+    ```
+
+    ## Connecting
+
+    ```python
+    import acme
+    conn = acme.connect()
+    ```
+
+    > [!WARNING]
+    > Connections are not thread-safe.
+    "###);
+}
+
+#[test]
+fn preset_gitbook() {
+    let md = preset_fixture(include_str!("fixtures/gitbook.html"));
+    assert_snapshot!(md, @r###"
+    # Authentication
+
+    Every request needs an API key in the `Authorization` header.
+
+    ```bash
+    curl -H "Authorization: Bearer $ACME_KEY" https://api.acme.dev/v1/me
+    ```
+
+    > [!WARNING]
+    > Keys are shown only once.
+    "###);
+}
+
+#[test]
+fn preset_nextra() {
+    let md = preset_fixture(include_str!("fixtures/nextra.html"));
+    assert_snapshot!(md, @r###"
+    # Deploying
+
+    Acme apps deploy as a single container.
+
+    ## Vercel
+
+    **CLI**
+
+    ```sh
+    vercel deploy
+    ```
+
+    **Git**
+
+    Connect the repository in the Vercel dashboard.
+
+    > [!TIP]
+    > Set `ACME_KEY` before deploying.
+
+    [Installation](/docs/installation)
+    "###);
+}
+
+#[test]
+fn preset_mintlify() {
+    let md = preset_fixture(include_str!("fixtures/mintlify.html"));
+    assert_snapshot!(md, @r###"
+    # Webhooks
+
+    Get notified when events happen in Acme.
+
+    ## Verifying signatures
+
+    **Node.js**
+
+    ```javascript
+    const ok = acme.verify(payload, signature);
+    ```
+
+    **Python**
+
+    ```python
+    ok = acme.verify(payload, signature)
+    ```
+
+    > [!NOTE]
+    > Signatures expire after five minutes.
+    "###);
+}
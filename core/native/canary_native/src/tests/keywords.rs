@@ -7,7 +7,7 @@ use insta::assert_debug_snapshot;
 #[test]
 fn extract() {
     let html = include_str_from_url!("https://docs.litellm.ai/docs/budget_manager");
-    let md = html::to_md(&html).unwrap();
+    let md = html::to_md(html, &html::Options::default()).unwrap();
     let mut words = keywords::extract(&md, 30).unwrap();
     words.sort();
 
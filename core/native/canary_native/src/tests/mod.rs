@@ -1,4 +1,7 @@
 pub mod chunk;
+pub mod front_matter;
 pub mod git;
 pub mod html;
 pub mod keywords;
+pub mod pipeline;
+pub mod tokenizer;
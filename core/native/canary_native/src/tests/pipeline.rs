@@ -0,0 +1,30 @@
+use crate::pipeline;
+
+#[test]
+fn process_pages() {
+    let pages = vec![
+        (
+            "https://example.com/a".to_string(),
+            "<h1>Alpha</h1><p>Routing requests across deployments.</p>".to_string(),
+        ),
+        (
+            "https://example.com/b".to_string(),
+            "<h1>Beta</h1><p>Budget manager for projects.</p>".to_string(),
+        ),
+    ];
+
+    let results = pipeline::process_pages(&pages, &pipeline::Options::default());
+    let urls: Vec<_> = results
+        .iter()
+        .map(|result| result.as_ref().unwrap().url.as_str())
+        .collect();
+    assert_eq!(urls, vec!["https://example.com/a", "https://example.com/b"]);
+
+    let page = results[0].as_ref().unwrap();
+    assert_eq!(
+        page.markdown,
+        "# Alpha\n\nRouting requests across deployments."
+    );
+    assert_eq!(page.chunks.len(), 1);
+    assert_eq!(page.chunks[0].content, page.markdown);
+}
@@ -0,0 +1,197 @@
+use crate::{
+    chunk,
+    tokenizer::{Side, Tokenizer},
+};
+
+use nanoid::nanoid;
+use std::env::temp_dir;
+
+const TOKENIZER_JSON: &str = r#"{
+  "version": "1.0",
+  "truncation": null,
+  "padding": null,
+  "added_tokens": [],
+  "normalizer": null,
+  "pre_tokenizer": { "type": "Whitespace" },
+  "post_processor": null,
+  "decoder": null,
+  "model": {
+    "type": "WordLevel",
+    "vocab": { "[UNK]": 0, "hello": 1, "world": 2 },
+    "unk_token": "[UNK]"
+  }
+}"#;
+
+#[test]
+fn count() {
+    let text = "Hello, wörld! Hello again.";
+
+    assert_eq!(
+        Tokenizer::load("characters").unwrap().count(text).unwrap(),
+        26
+    );
+    assert_eq!(Tokenizer::load("words").unwrap().count(text).unwrap(), 4);
+
+    for spec in ["cl100k", "o200k", "p50k"] {
+        let tokens = Tokenizer::load(spec).unwrap().count(text).unwrap();
+        assert!(tokens > 4 && tokens < 26, "{spec}: {tokens}");
+    }
+}
+
+#[test]
+fn hugging_face() {
+    let path = temp_dir().join(format!("{}.json", nanoid!()));
+    std::fs::write(&path, TOKENIZER_JSON).unwrap();
+
+    let tokenizer = Tokenizer::load(path.to_str().unwrap()).unwrap();
+    assert_eq!(tokenizer.count("hello world , again").unwrap(), 4);
+
+    let error = Tokenizer::load("/does/not/exist/tokenizer.json")
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), "tokenizer");
+}
+
+#[test]
+fn hugging_face_encode_error() {
+    // No `[UNK]` in the vocabulary, so unknown words fail to encode.
+    let path = temp_dir().join(format!("{}.json", nanoid!()));
+    std::fs::write(&path, TOKENIZER_JSON.replace(r#""[UNK]": 0, "#, "")).unwrap();
+    let spec = path.to_str().unwrap();
+    let tokenizer = Tokenizer::load(spec).unwrap();
+
+    assert_eq!(tokenizer.count("hello world").unwrap(), 2);
+    assert_eq!(
+        tokenizer.count("hello there").unwrap_err().kind(),
+        "tokenizer"
+    );
+    assert_eq!(
+        tokenizer
+            .truncate("hello there world", 1, Side::Tail)
+            .unwrap_err()
+            .kind(),
+        "tokenizer"
+    );
+
+    let opts = chunk::Options {
+        tokenizer: spec.to_string(),
+        ..chunk::Options::new(20)
+    };
+    let error = chunk::chunk_text("hello there", &opts).unwrap_err();
+    assert_eq!(error.kind(), "tokenizer");
+    // The error is not left behind for the next call.
+    assert!(chunk::chunk_text("hello world", &opts).is_ok());
+}
+
+#[test]
+fn hugging_face_encode_error_shared_splitter() {
+    let path = temp_dir().join(format!("{}.json", nanoid!()));
+    std::fs::write(&path, TOKENIZER_JSON.replace(r#""[UNK]": 0, "#, "")).unwrap();
+    let opts = chunk::Options {
+        tokenizer: path.to_str().unwrap().to_string(),
+        ..chunk::Options::new(20)
+    };
+    let splitter = chunk::Splitter::text(&opts).unwrap();
+
+    // Each split sees only its own encoding errors.
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for _ in 0..1000 {
+                assert!(splitter.chunks("hello there").is_err());
+            }
+        });
+        scope.spawn(|| {
+            for _ in 0..1000 {
+                let chunks = splitter.chunks("hello world").unwrap();
+                assert_eq!(chunks[0].tokens, 2);
+            }
+        });
+    });
+}
+
+#[test]
+fn chunk_with_tokenizer() {
+    let opts = chunk::Options {
+        tokenizer: "characters".to_string(),
+        ..chunk::Options::new(20)
+    };
+    let result = chunk::chunk_text("The quick brown fox jumps over the lazy dog.", &opts).unwrap();
+
+    assert!(result.len() > 1);
+    assert!(result.iter().all(|c| c.tokens == c.content.chars().count()));
+    assert!(result.iter().all(|c| c.tokens <= 20));
+}
+
+#[test]
+fn truncate() {
+    let text = "First sentence here. Second sentence follows. Third one ends it.";
+    let words = Tokenizer::load("words").unwrap();
+
+    assert_eq!(words.truncate(text, 100, Side::Tail).unwrap(), text);
+    assert_eq!(
+        words.truncate(text, 6, Side::Tail).unwrap(),
+        "First sentence here. Second sentence follows."
+    );
+    assert_eq!(
+        words.truncate(text, 7, Side::Head).unwrap(),
+        "Second sentence follows. Third one ends it."
+    );
+    assert_eq!(
+        words.truncate(text, 6, Side::Middle).unwrap(),
+        "First sentence here.\n…\none ends it."
+    );
+
+    // The whole budget is used, even past the last sentence that fits.
+    assert_eq!(
+        words.truncate(text, 8, Side::Tail).unwrap(),
+        "First sentence here. Second sentence follows. Third one"
+    );
+    assert_eq!(
+        words.truncate(text, 2, Side::Tail).unwrap(),
+        "First sentence"
+    );
+    // Suffixes start at a word, not at the punctuation before it.
+    assert_eq!(words.truncate(text, 2, Side::Head).unwrap(), "ends it.");
+
+    // Never splits a multi-byte character.
+    let characters = Tokenizer::load("characters").unwrap();
+    assert_eq!(
+        characters.truncate("héllo wörld", 2, Side::Tail).unwrap(),
+        "hé"
+    );
+
+    let cl100k = Tokenizer::load("cl100k").unwrap();
+    for max_tokens in [8, 12] {
+        for side in [Side::Head, Side::Tail] {
+            let truncated = cl100k.truncate(text, max_tokens, side).unwrap();
+            let tokens = cl100k.count(&truncated).unwrap();
+            assert!(
+                tokens <= max_tokens && tokens + 2 >= max_tokens,
+                "{side:?} {max_tokens}: {truncated:?}"
+            );
+        }
+    }
+    let truncated = cl100k.truncate(text, 10, Side::Head).unwrap();
+    assert!(text.ends_with(&truncated));
+}
+
+#[test]
+fn truncate_middle_small_budgets() {
+    let text = "First sentence here. Second sentence follows. Third one ends it.";
+    let cl100k = Tokenizer::load("cl100k").unwrap();
+
+    // Too small for an elision with a word on both sides: keep the start.
+    assert_eq!(cl100k.truncate(text, 1, Side::Middle).unwrap(), "First");
+    assert_eq!(
+        cl100k.truncate(text, 3, Side::Middle).unwrap(),
+        "First sentence here"
+    );
+    assert_eq!(
+        cl100k.truncate(text, 5, Side::Middle).unwrap(),
+        "First sentence here. Second"
+    );
+    assert_eq!(
+        cl100k.truncate(text, 8, Side::Middle).unwrap(),
+        "First sentence here\n…\nends it."
+    );
+}
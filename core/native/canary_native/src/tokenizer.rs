@@ -0,0 +1,251 @@
+//! Token counting used to size chunks.
+//!
+//! A tokenizer is picked with a short spec string:
+//!
+//! - `"cl100k"`, `"o200k"`, `"p50k"`: tiktoken encodings
+//! - `"characters"`: Unicode scalar values
+//! - `"words"`: Unicode words
+//! - anything else: path to a HuggingFace `tokenizer.json`
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use text_splitter::ChunkSizer;
+use tiktoken_rs::{cl100k_base, o200k_base, p50k_base, CoreBPE};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Spec used when none is given.
+pub const DEFAULT: &str = "cl100k";
+
+/// Errors returned while loading a tokenizer or counting tokens.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to load tokenizer {spec:?}: {message}")]
+    Load { spec: String, message: String },
+    #[error("failed to count tokens: {0}")]
+    Encode(String),
+}
+
+impl Error {
+    /// Short machine-readable name of the error, e.g. `"tokenizer"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Load { .. } | Error::Encode(_) => "tokenizer",
+        }
+    }
+}
+
+/// Where [`Tokenizer::truncate`] removes text from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// Drop the beginning and keep the end.
+    Head,
+    /// Drop the end and keep the beginning.
+    Tail,
+    /// Keep both ends and drop what is in between.
+    Middle,
+}
+
+/// Placed where [`Side::Middle`] truncation removed text.
+const ELISION: &str = "\n…\n";
+
+/// A loaded tokenizer. Cheap to clone.
+#[derive(Clone)]
+pub enum Tokenizer {
+    Tiktoken(&'static CoreBPE),
+    HuggingFace {
+        tokenizer: Arc<tokenizers::Tokenizer>,
+        /// First encoding error hit while sizing, which [`ChunkSizer`]
+        /// cannot return. Shared by clones other than [`Tokenizer::fresh`]
+        /// ones, taken by [`Tokenizer::check`].
+        failure: Arc<Mutex<Option<String>>>,
+    },
+    Characters,
+    Words,
+}
+
+impl Tokenizer {
+    /// Loads the tokenizer described by `spec`. Loaded tokenizers are cached
+    /// for the lifetime of the process.
+    pub fn load(spec: &str) -> Result<Self, Error> {
+        let error = |message: String| Error::Load {
+            spec: spec.to_string(),
+            message,
+        };
+
+        match spec {
+            "cl100k" => tiktoken(&CL100K, cl100k_base).map_err(error),
+            "o200k" => tiktoken(&O200K, o200k_base).map_err(error),
+            "p50k" => tiktoken(&P50K, p50k_base).map_err(error),
+            "characters" => Ok(Self::Characters),
+            "words" => Ok(Self::Words),
+            path => hugging_face(Path::new(path)).map_err(error),
+        }
+    }
+
+    /// Number of tokens in `text`.
+    pub fn count(&self, text: &str) -> Result<usize, Error> {
+        Ok(match self {
+            Self::Tiktoken(bpe) => bpe.encode_ordinary(text).len(),
+            Self::HuggingFace { tokenizer, .. } => tokenizer
+                .encode(text, false)
+                .map(|encoding| encoding.len())
+                .map_err(|e| Error::Encode(e.to_string()))?,
+            Self::Characters => text.chars().count(),
+            Self::Words => text.unicode_words().count(),
+        })
+    }
+
+    /// A clone with its own failure slot, so concurrent calls sharing a
+    /// tokenizer each [`check`](Tokenizer::check) only their own errors.
+    pub fn fresh(&self) -> Self {
+        match self {
+            Self::HuggingFace { tokenizer, .. } => Self::HuggingFace {
+                tokenizer: tokenizer.clone(),
+                failure: Arc::default(),
+            },
+            _ => self.clone(),
+        }
+    }
+
+    /// Fails with the first error [`ChunkSizer::size`] hit since the last
+    /// check, and clears it.
+    pub fn check(&self) -> Result<(), Error> {
+        match self {
+            Self::HuggingFace { failure, .. } => match failure.lock().unwrap().take() {
+                Some(message) => Err(Error::Encode(message)),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Shortens `text` to at most `max_tokens` by removing text from `side`.
+    ///
+    /// Cuts fall between words, and only between characters when not even
+    /// one word fits. [`Side::Middle`] keeps just the beginning when either
+    /// side of the elision would be left without a word.
+    pub fn truncate(&self, text: &str, max_tokens: usize, side: Side) -> Result<String, Error> {
+        self.fresh().truncate_checked(text, max_tokens, side)
+    }
+
+    fn truncate_checked(&self, text: &str, max_tokens: usize, side: Side) -> Result<String, Error> {
+        let text = text.trim();
+        if self.count(text)? <= max_tokens {
+            return Ok(text.to_string());
+        }
+
+        let truncated = match side {
+            Side::Head => self.suffix(text, max_tokens).to_string(),
+            Side::Tail => self.prefix(text, max_tokens).to_string(),
+            Side::Middle => {
+                let budget = max_tokens.saturating_sub(self.size(ELISION));
+                // Whatever the head leaves unused goes to the tail.
+                let head = self.prefix(text, budget - budget / 2);
+                let tail = self.suffix(&text[head.len()..], budget - self.size(head));
+
+                if has_word(head) && has_word(tail) {
+                    format!("{head}{ELISION}{tail}")
+                } else {
+                    self.prefix(text, max_tokens).to_string()
+                }
+            }
+        };
+
+        self.check()?;
+        Ok(truncated)
+    }
+
+    /// Longest prefix of `text` within `max_tokens`.
+    fn prefix<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        let word_ends = text
+            .split_word_bound_indices()
+            .map(|(i, word)| i + word.len());
+        let char_ends = text.char_indices().map(|(i, c)| i + c.len_utf8());
+
+        for ends in [word_ends.collect::<Vec<_>>(), char_ends.collect()] {
+            let fits = ends.partition_point(|&end| self.size(&text[..end]) <= max_tokens);
+            if fits > 0 {
+                return text[..ends[fits - 1]].trim_end();
+            }
+        }
+
+        ""
+    }
+
+    /// Longest suffix of `text` within `max_tokens`, starting at a word
+    /// rather than at the punctuation after one.
+    fn suffix<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        let word_starts = text
+            .split_word_bound_indices()
+            .filter(|(_, word)| word.chars().next().is_some_and(char::is_alphanumeric))
+            .map(|(i, _)| i);
+        let char_starts = text.char_indices().map(|(i, _)| i);
+
+        for starts in [word_starts.collect::<Vec<_>>(), char_starts.collect()] {
+            let too_big = starts.partition_point(|&start| self.size(&text[start..]) > max_tokens);
+            if too_big < starts.len() {
+                return text[starts[too_big]..].trim_start();
+            }
+        }
+
+        ""
+    }
+}
+
+fn has_word(text: &str) -> bool {
+    text.unicode_words().next().is_some()
+}
+
+impl ChunkSizer for Tokenizer {
+    /// Records encoding errors for [`Tokenizer::check`] and sizes the chunk
+    /// by its bytes meanwhile, which no tokenizer exceeds in practice.
+    fn size(&self, chunk: &str) -> usize {
+        self.count(chunk).unwrap_or_else(|error| {
+            if let (Self::HuggingFace { failure, .. }, Error::Encode(message)) = (self, error) {
+                failure.lock().unwrap().get_or_insert(message);
+            }
+            chunk.len()
+        })
+    }
+}
+
+static CL100K: OnceLock<CoreBPE> = OnceLock::new();
+static O200K: OnceLock<CoreBPE> = OnceLock::new();
+static P50K: OnceLock<CoreBPE> = OnceLock::new();
+
+/// Building the BPE tables is by far the most expensive part of chunking,
+/// so each encoding is loaded once per process.
+fn tiktoken<E: std::fmt::Display>(
+    cell: &'static OnceLock<CoreBPE>,
+    init: fn() -> Result<CoreBPE, E>,
+) -> Result<Tokenizer, String> {
+    if let Some(bpe) = cell.get() {
+        return Ok(Tokenizer::Tiktoken(bpe));
+    }
+
+    let bpe = init().map_err(|e| e.to_string())?;
+    Ok(Tokenizer::Tiktoken(cell.get_or_init(|| bpe)))
+}
+
+fn hugging_face(path: &Path) -> Result<Tokenizer, String> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Arc<tokenizers::Tokenizer>>>> = OnceLock::new();
+
+    let key = path.to_string_lossy().into_owned();
+    let cache = CACHE.get_or_init(Default::default);
+
+    let loaded = |tokenizer| Tokenizer::HuggingFace {
+        tokenizer,
+        failure: Arc::default(),
+    };
+    if let Some(tokenizer) = cache.lock().unwrap().get(&key) {
+        return Ok(loaded(tokenizer.clone()));
+    }
+
+    let tokenizer = tokenizers::Tokenizer::from_file(path).map_err(|e| e.to_string())?;
+    let tokenizer = Arc::new(tokenizer);
+    cache.lock().unwrap().insert(key, tokenizer.clone());
+
+    Ok(loaded(tokenizer))
+}
@@ -0,0 +1,26 @@
+defmodule Canary.Test.Native do
+  use ExUnit.Case, async: true
+
+  describe "options" do
+    test "keyword lists" do
+      {:ok, chunks} =
+        Canary.Native.chunk_text("one two three four", tokenizer: "words", max_tokens: 2)
+      assert Enum.map(chunks, & &1.content) == ["one two", "three four"]
+    end
+
+    test "invalid values" do
+      assert {:error, {:invalid_options, _}} =
+               Canary.Native.chunk_text("text", %{max_tokens: -1})
+
+      assert {:error, {:invalid_options, _}} =
+               Canary.Native.chunk_text("text", %{max_tokens: "ten"})
+
+      assert {:error, {:invalid_options, _}} = Canary.Native.html_to_md("<p>text</p>", "opts")
+
+      assert [{:error, {:invalid_options, _}}] =
+               Canary.Native.process_pages([{"https://example.com", "<p>text</p>"}], %{
+                 max_keywords: :many
+               })
+    end
+  end
+end
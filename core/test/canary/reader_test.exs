@@ -15,4 +15,44 @@ defmodule Canary.Test.Reader do
 
     assert Canary.Reader.title_from_html(html) == "Canary"
   end
+
+  test "page_meta" do
+    html = """
+    <!doctype html>
+    <html lang="en">
+      <head>
+        <title>Canary</title>
+        <meta name="description" content="Search for your docs">
+        <meta name="robots" content="noindex">
+        <link rel="canonical" href="/docs/intro">
+      </head>
+    </html>
+    """
+
+    assert {:ok, meta} = Canary.Reader.page_meta(html, "https://getcanary.dev/docs/intro/")
+    assert meta.title == "Canary"
+    assert meta.description == "Search for your docs"
+    assert meta.canonical == "https://getcanary.dev/docs/intro"
+    assert meta.lang == "en"
+    assert meta.noindex
+  end
+
+  test "chunk_markdown_document" do
+    content = """
+    ---
+    title: Getting Started
+    tags: [setup]
+    ---
+
+    Run the installer.
+    """
+
+    assert {:ok, %{front_matter: %{tags: ["setup"]}, chunks: [chunk]}} =
+             Canary.Reader.chunk_markdown_document(content)
+
+    assert chunk.content == "Run the installer."
+    assert chunk.text == "Getting Started\n\nRun the installer."
+
+    assert Canary.Reader.chunk_markdown_document("---\ndraft: true\n---\nWIP") == :skip
+  end
 end
@@ -55,4 +55,13 @@ defmodule Canary.Test.Scraper do
     items = Scraper.run(html)
     assert length(items) == 6
   end
+
+  test "keeps non-ascii text" do
+    html = "<h1 id=\"guide\">Guía</h1><p>日本語のドキュメント</p>"
+
+    assert [%Canary.Scraper.Item{id: "guide", level: 1, title: "Guía", content: content}] =
+             Scraper.run(html)
+
+    assert content == "# Guía\n\n日本語のドキュメント"
+  end
 end
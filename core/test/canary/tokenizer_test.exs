@@ -2,11 +2,29 @@ defmodule Canary.Test.Tokenizer do
   use ExUnit.Case, async: true
   alias Canary.Tokenizer
 
-  describe "count_tokens/1" do
-    test "llama" do
-      tokenizer = Tokenizer.load!(:llama_2)
-      num = "Hello world" |> Tokenizer.count_tokens(tokenizer)
-      assert num == 3
+  describe "count_tokens/2" do
+    test "cl100k" do
+      assert Tokenizer.count_tokens("Hello world", "cl100k") == {:ok, 2}
+    end
+
+    test "unknown tokenizer" do
+      assert {:error, {:tokenizer, _}} = Tokenizer.count_tokens("Hello world", "missing.json")
+    end
+  end
+
+  describe "truncate/4" do
+    test "keeps the end by default" do
+      text = "First sentence here. Second sentence follows. Third one ends it."
+
+      assert Tokenizer.truncate(text, "words", 7) ==
+               {:ok, "Second sentence follows. Third one ends it."}
+
+      assert Tokenizer.truncate(text, "words", 7, :tail) ==
+               {:ok, "First sentence here. Second sentence follows."}
+    end
+
+    test "unknown tokenizer" do
+      assert {:error, {:tokenizer, _}} = Tokenizer.truncate("Hello world", "missing.json", 1)
     end
   end
 end